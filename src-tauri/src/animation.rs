@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::{Entry, HashMap};
+
+use crate::tex::{self, Tex, TexFrame};
+
+// 动画贴图的导出格式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationFormat {
+    // 不生成动画，只导出图集
+    None,
+    #[default]
+    Gif,
+    Apng,
+    Webp,
+}
+
+impl AnimationFormat {
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            AnimationFormat::None => None,
+            AnimationFormat::Gif => Some("gif"),
+            AnimationFormat::Apng => Some("png"),
+            AnimationFormat::Webp => Some("webp"),
+        }
+    }
+}
+
+// 从图集中切出的一帧 RGBA8 图像
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    pub delay_ms: u32,
+}
+
+// 按 TEXS 帧信息切分图集，所有帧统一为画布尺寸
+// 帧坐标对应原始尺寸，因此始终使用第一级 mipmap
+pub fn extract_frames(tex: &Tex) -> Result<Vec<Frame>, String> {
+    let frame_info = tex.frame_info.as_ref().ok_or("TEX文件不包含帧信息")?;
+    if frame_info.frames.is_empty() {
+        return Err("TEX帧信息为空".to_string());
+    }
+
+    let mut atlases = HashMap::new();
+    let mut frames = Vec::new();
+
+    for info in &frame_info.frames {
+        let index = info.image_id as usize;
+        let (atlas_width, atlas_height, atlas) = match atlases.entry(index) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let image = tex::decode_image(tex, index, 0)?;
+                entry.insert((image.width, image.height, image.to_rgba()))
+            }
+        };
+        frames.push(slice_frame(info, *atlas_width, *atlas_height, atlas)?);
+    }
+
+    let (canvas_width, canvas_height) = if frame_info.gif_width > 0 && frame_info.gif_height > 0 {
+        (frame_info.gif_width, frame_info.gif_height)
+    } else {
+        (frames[0].width, frames[0].height)
+    };
+
+    Ok(frames
+        .into_iter()
+        .map(|frame| fit_canvas(frame, canvas_width, canvas_height))
+        .collect())
+}
+
+fn slice_frame(info: &TexFrame, atlas_width: u32, atlas_height: u32, atlas: &[u8]) -> Result<Frame, String> {
+    // 宽高为 0 表示帧旋转存放，真实尺寸保存在另一组字段里
+    let width = if info.width != 0.0 { info.width } else { info.height_x };
+    let height = if info.height != 0.0 { info.height } else { info.width_y };
+
+    let x = info.x.min(info.x + width).round().max(0.0) as u32;
+    let y = info.y.min(info.y + height).round().max(0.0) as u32;
+    let crop_width = width.abs().round() as u32;
+    let crop_height = height.abs().round() as u32;
+
+    if crop_width == 0 || crop_height == 0 || x + crop_width > atlas_width || y + crop_height > atlas_height {
+        return Err("TEX帧区域超出图集范围".to_string());
+    }
+
+    let mut pixels = Vec::with_capacity((crop_width * crop_height * 4) as usize);
+    for row in y..y + crop_height {
+        let start = ((row * atlas_width + x) * 4) as usize;
+        pixels.extend_from_slice(&atlas[start..start + (crop_width * 4) as usize]);
+    }
+
+    let frame = Frame {
+        width: crop_width,
+        height: crop_height,
+        pixels,
+        delay_ms: (info.frametime.max(0.0) * 1000.0).round() as u32,
+    };
+
+    // 由宽高的正负号决定旋转方向
+    Ok(match (width < 0.0, height < 0.0) {
+        (false, false) => frame,
+        (true, false) => rotate_counterclockwise(frame),
+        (false, true) => rotate_clockwise(frame),
+        (true, true) => rotate_clockwise(rotate_clockwise(frame)),
+    })
+}
+
+fn rotate_clockwise(frame: Frame) -> Frame {
+    let (w, h) = (frame.width as usize, frame.height as usize);
+    let mut pixels = vec![0u8; w * h * 4];
+    for y in 0..h {
+        for x in 0..w {
+            let src = (y * w + x) * 4;
+            let dst = (x * h + (h - 1 - y)) * 4;
+            pixels[dst..dst + 4].copy_from_slice(&frame.pixels[src..src + 4]);
+        }
+    }
+
+    Frame {
+        width: frame.height,
+        height: frame.width,
+        pixels,
+        delay_ms: frame.delay_ms,
+    }
+}
+
+fn rotate_counterclockwise(frame: Frame) -> Frame {
+    rotate_clockwise(rotate_clockwise(rotate_clockwise(frame)))
+}
+
+// 将帧放到画布左上角，超出部分裁掉，空白部分透明
+fn fit_canvas(frame: Frame, width: u32, height: u32) -> Frame {
+    if frame.width == width && frame.height == height {
+        return frame;
+    }
+
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let copy_width = frame.width.min(width) as usize * 4;
+    for row in 0..frame.height.min(height) as usize {
+        let src = row * frame.width as usize * 4;
+        let dst = row * width as usize * 4;
+        pixels[dst..dst + copy_width].copy_from_slice(&frame.pixels[src..src + copy_width]);
+    }
+
+    Frame {
+        width,
+        height,
+        pixels,
+        delay_ms: frame.delay_ms,
+    }
+}
+
+pub fn encode(format: AnimationFormat, frames: &[Frame]) -> Result<Vec<u8>, String> {
+    match format {
+        AnimationFormat::None => Err("未指定动画导出格式".to_string()),
+        AnimationFormat::Gif => encode_gif(frames),
+        AnimationFormat::Apng => encode_apng(frames),
+        AnimationFormat::Webp => encode_webp(frames),
+    }
+}
+
+pub fn encode_frame_png(frame: &Frame) -> Result<Vec<u8>, String> {
+    tex::encode_png(&tex::DecodedImage {
+        width: frame.width,
+        height: frame.height,
+        color: tex::ColorType::Rgba,
+        pixels: frame.pixels.clone(),
+    })
+}
+
+fn encode_gif(frames: &[Frame]) -> Result<Vec<u8>, String> {
+    let first = &frames[0];
+    let width = u16::try_from(first.width).map_err(|_| "图像尺寸超出GIF限制".to_string())?;
+    let height = u16::try_from(first.height).map_err(|_| "图像尺寸超出GIF限制".to_string())?;
+
+    let mut buffer = Vec::new();
+    {
+        let mut encoder =
+            gif::Encoder::new(&mut buffer, width, height, &[]).map_err(|e| format!("GIF编码失败: {}", e))?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|e| format!("GIF编码失败: {}", e))?;
+
+        for frame in frames {
+            let mut pixels = frame.pixels.clone();
+            let mut gif_frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+            // GIF 的帧延迟单位为 1/100 秒
+            gif_frame.delay = (frame.delay_ms / 10).min(u16::MAX as u32) as u16;
+            gif_frame.dispose = gif::DisposalMethod::Background;
+            encoder
+                .write_frame(&gif_frame)
+                .map_err(|e| format!("GIF编码失败: {}", e))?;
+        }
+    }
+
+    Ok(buffer)
+}
+
+fn encode_apng(frames: &[Frame]) -> Result<Vec<u8>, String> {
+    let first = &frames[0];
+    let mut buffer = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buffer, first.width, first.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(|e| format!("APNG编码失败: {}", e))?;
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("APNG编码失败: {}", e))?;
+    for frame in frames {
+        writer
+            .set_frame_delay(frame.delay_ms.min(u16::MAX as u32) as u16, 1000)
+            .map_err(|e| format!("APNG编码失败: {}", e))?;
+        writer
+            .write_image_data(&frame.pixels)
+            .map_err(|e| format!("APNG编码失败: {}", e))?;
+    }
+    writer.finish().map_err(|e| format!("APNG编码失败: {}", e))?;
+
+    Ok(buffer)
+}
+
+// 动画 WebP：VP8X + ANIM + 每帧一个 ANMF 块，帧数据使用无损 VP8L 编码
+fn encode_webp(frames: &[Frame]) -> Result<Vec<u8>, String> {
+    let first = &frames[0];
+    let mut chunks = Vec::new();
+
+    let mut vp8x = vec![0x12, 0, 0, 0]; // 动画 + alpha 标记
+    vp8x.extend_from_slice(&(first.width - 1).to_le_bytes()[..3]);
+    vp8x.extend_from_slice(&(first.height - 1).to_le_bytes()[..3]);
+    write_riff_chunk(&mut chunks, b"VP8X", &vp8x);
+
+    // 背景色透明，无限循环
+    write_riff_chunk(&mut chunks, b"ANIM", &[0, 0, 0, 0, 0, 0]);
+
+    for frame in frames {
+        let mut still = Vec::new();
+        image_webp::WebPEncoder::new(&mut still)
+            .encode(&frame.pixels, frame.width, frame.height, image_webp::ColorType::Rgba8)
+            .map_err(|e| format!("WebP编码失败: {}", e))?;
+
+        // 简单格式的 WebP 为 12 字节 RIFF 头 + VP8L 块，这里取出 VP8L 块作为帧数据
+        let mut anmf = vec![0u8; 6]; // 帧偏移 X/Y 均为 0
+        anmf.extend_from_slice(&(frame.width - 1).to_le_bytes()[..3]);
+        anmf.extend_from_slice(&(frame.height - 1).to_le_bytes()[..3]);
+        anmf.extend_from_slice(&frame.delay_ms.min(0xff_ffff).to_le_bytes()[..3]);
+        anmf.push(0x02); // 不混合，不清除
+        anmf.extend_from_slice(&still[12..]);
+        write_riff_chunk(&mut chunks, b"ANMF", &anmf);
+    }
+
+    let mut buffer = Vec::with_capacity(chunks.len() + 12);
+    buffer.extend_from_slice(b"RIFF");
+    buffer.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+    buffer.extend_from_slice(b"WEBP");
+    buffer.extend_from_slice(&chunks);
+    Ok(buffer)
+}
+
+fn write_riff_chunk(buffer: &mut Vec<u8>, fourcc: &[u8; 4], data: &[u8]) {
+    buffer.extend_from_slice(fourcc);
+    buffer.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        buffer.push(0);
+    }
+}
@@ -0,0 +1,143 @@
+// DXT1/DXT3/DXT5（BC1/BC2/BC3）块压缩贴图的软件解码，输出 RGBA8 像素
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockFormat {
+    Dxt1,
+    Dxt3,
+    Dxt5,
+}
+
+impl BlockFormat {
+    fn block_size(self) -> usize {
+        match self {
+            BlockFormat::Dxt1 => 8,
+            BlockFormat::Dxt3 | BlockFormat::Dxt5 => 16,
+        }
+    }
+}
+
+pub fn decode(format: BlockFormat, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let width = width as usize;
+    let height = height as usize;
+    let blocks_x = width.div_ceil(4);
+    let blocks_y = height.div_ceil(4);
+    let block_size = format.block_size();
+
+    let expected = blocks_x * blocks_y * block_size;
+    if data.len() < expected {
+        return Err(format!("{:?} 数据不完整: 需要 {} 字节，实际 {} 字节", format, expected, data.len()));
+    }
+
+    let mut pixels = vec![0u8; width * height * 4];
+    let mut block_pixels = [[0u8; 4]; 16];
+
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            let offset = (by * blocks_x + bx) * block_size;
+            let block = &data[offset..offset + block_size];
+
+            match format {
+                BlockFormat::Dxt1 => decode_color_block(block, true, &mut block_pixels),
+                BlockFormat::Dxt3 => {
+                    decode_color_block(&block[8..], false, &mut block_pixels);
+                    decode_explicit_alpha(&block[..8], &mut block_pixels);
+                }
+                BlockFormat::Dxt5 => {
+                    decode_color_block(&block[8..], false, &mut block_pixels);
+                    decode_interpolated_alpha(&block[..8], &mut block_pixels);
+                }
+            }
+
+            // 写回图像，裁掉超出边界的像素
+            for (i, pixel) in block_pixels.iter().enumerate() {
+                let x = bx * 4 + i % 4;
+                let y = by * 4 + i / 4;
+                if x < width && y < height {
+                    let index = (y * width + x) * 4;
+                    pixels[index..index + 4].copy_from_slice(pixel);
+                }
+            }
+        }
+    }
+
+    Ok(pixels)
+}
+
+fn rgb565(value: u16) -> [u8; 3] {
+    let r = ((value >> 11) & 0x1f) as u8;
+    let g = ((value >> 5) & 0x3f) as u8;
+    let b = (value & 0x1f) as u8;
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+// 颜色块：两个 RGB565 端点 + 16 个 2 位索引
+// DXT1 在 color0 <= color1 时使用三色模式，第四种颜色为透明
+fn decode_color_block(block: &[u8], allow_transparent: bool, out: &mut [[u8; 4]; 16]) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let e0 = rgb565(c0);
+    let e1 = rgb565(c1);
+
+    let mut palette = [[0u8; 4]; 4];
+    palette[0] = [e0[0], e0[1], e0[2], 255];
+    palette[1] = [e1[0], e1[1], e1[2], 255];
+
+    if c0 > c1 || !allow_transparent {
+        for channel in 0..3 {
+            let a = e0[channel] as u16;
+            let b = e1[channel] as u16;
+            palette[2][channel] = ((2 * a + b) / 3) as u8;
+            palette[3][channel] = ((a + 2 * b) / 3) as u8;
+        }
+        palette[2][3] = 255;
+        palette[3][3] = 255;
+    } else {
+        for channel in 0..3 {
+            palette[2][channel] = ((e0[channel] as u16 + e1[channel] as u16) / 2) as u8;
+        }
+        palette[2][3] = 255;
+        palette[3] = [0, 0, 0, 0];
+    }
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, pixel) in out.iter_mut().enumerate() {
+        *pixel = palette[((indices >> (i * 2)) & 0x3) as usize];
+    }
+}
+
+// DXT3：每个像素 4 位的显式 alpha
+fn decode_explicit_alpha(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    for (i, pixel) in out.iter_mut().enumerate() {
+        let nibble = (block[i / 2] >> ((i % 2) * 4)) & 0x0f;
+        pixel[3] = nibble * 17;
+    }
+}
+
+// DXT5：两个 alpha 端点 + 16 个 3 位索引
+fn decode_interpolated_alpha(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    let a0 = block[0] as u16;
+    let a1 = block[1] as u16;
+
+    let mut palette = [0u8; 8];
+    palette[0] = a0 as u8;
+    palette[1] = a1 as u8;
+    if a0 > a1 {
+        for i in 1..7u16 {
+            palette[i as usize + 1] = (((7 - i) * a0 + i * a1) / 7) as u8;
+        }
+    } else {
+        for i in 1..5u16 {
+            palette[i as usize + 1] = (((5 - i) * a0 + i * a1) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+
+    let mut indices = 0u64;
+    for (i, byte) in block[2..8].iter().enumerate() {
+        indices |= (*byte as u64) << (i * 8);
+    }
+    for (i, pixel) in out.iter_mut().enumerate() {
+        pixel[3] = palette[((indices >> (i * 3)) & 0x7) as usize];
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+use crate::extract;
+use crate::jobs::JobStatus;
+
+// 所有 Tauri 命令统一返回的错误类型
+// 序列化为 {"code": "...", ...字段}，前端按 code 查找 errors.<code> 翻译，字段用作占位符
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum AppError {
+    // 文件或目录不存在
+    NotFound { path: String },
+    // 目标已存在且未允许覆盖
+    AlreadyExists { path: String },
+    // 读写文件或目录失败
+    Io { path: String, message: String },
+    // JSON 文件格式错误
+    InvalidJson { path: String, message: String },
+    // 命令参数不合法
+    InvalidInput { message: String },
+    // 无法获取用户主目录、数据目录等系统目录
+    DirectoryUnavailable { name: String },
+    // 窗口、链接、文件管理器等系统调用失败
+    System { message: String },
+    // PKG/TEX 解析、打包或原生提取失败
+    Package { message: String },
+    RepkgNotFound { path: String },
+    // RePKG 版本低于支持的最低版本
+    RepkgIncompatible { path: String, version: Option<String> },
+    RepkgFailed { exit_code: Option<i32>, stderr: String },
+    // RePKG 运行超时，进程树已被结束
+    RepkgTimeout { timeout_secs: u64 },
+    Cancelled,
+    JobNotFound { job_id: String },
+    InvalidJobState { job_id: String, status: JobStatus },
+    SteamNotFound,
+}
+
+impl AppError {
+    pub fn not_found(path: impl AsRef<Path>) -> Self {
+        AppError::NotFound {
+            path: path.as_ref().to_string_lossy().to_string(),
+        }
+    }
+
+    pub fn io(path: impl AsRef<Path>, error: impl fmt::Display) -> Self {
+        AppError::Io {
+            path: path.as_ref().to_string_lossy().to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn json(path: impl AsRef<Path>, error: impl fmt::Display) -> Self {
+        AppError::InvalidJson {
+            path: path.as_ref().to_string_lossy().to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn system(error: impl fmt::Display) -> Self {
+        AppError::System {
+            message: error.to_string(),
+        }
+    }
+
+    // PKG/TEX 模块内部仍返回字符串错误，在命令边界统一转换
+    pub fn package(message: String) -> Self {
+        if message == extract::CANCELLED {
+            return AppError::Cancelled;
+        }
+        AppError::Package { message }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound { path } => write!(f, "路径不存在: {}", path),
+            AppError::AlreadyExists { path } => write!(f, "目标已存在: {}", path),
+            AppError::Io { path, message } => write!(f, "文件操作失败 {}: {}", path, message),
+            AppError::InvalidJson { path, message } => write!(f, "JSON解析错误 {}: {}", path, message),
+            AppError::InvalidInput { message } => write!(f, "参数错误: {}", message),
+            AppError::DirectoryUnavailable { name } => write!(f, "无法获取{}目录", name),
+            AppError::System { message } => write!(f, "系统调用失败: {}", message),
+            AppError::Package { message } => write!(f, "{}", message),
+            AppError::RepkgNotFound { path } => write!(f, "RePKG Not Found: {}", path),
+            AppError::RepkgIncompatible { path, version } => {
+                write!(f, "RePKG 版本不兼容 ({:?}): {}", version, path)
+            }
+            AppError::RepkgFailed { exit_code, stderr } => {
+                write!(f, "RePKG 命令执行失败 ({:?}): {}", exit_code, stderr)
+            }
+            AppError::RepkgTimeout { timeout_secs } => write!(f, "RePKG 运行超过 {} 秒，已被终止", timeout_secs),
+            AppError::Cancelled => write!(f, "{}", extract::CANCELLED),
+            AppError::JobNotFound { job_id } => write!(f, "提取任务不存在: {}", job_id),
+            AppError::InvalidJobState { job_id, status } => {
+                write!(f, "任务 {} 当前状态 {:?} 不支持该操作", job_id, status)
+            }
+            AppError::SteamNotFound => write!(f, "未检测到Steam或Wallpaper Engine，请确认已正确安装"),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
@@ -0,0 +1,511 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::pkg::{self, EntryType, Package, PkgEntry, PkgReader};
+use crate::repkg::ExtractOptions;
+use crate::animation::{self, AnimationFormat};
+use crate::tex::{self, Tex, TexFormat};
+
+// 与 RePKG 保持一致的默认输出目录
+const DEFAULT_OUTPUT: &str = "./output";
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ExtractSummary {
+    pub packages: usize,
+    pub extracted: usize,
+    pub skipped: usize,
+    pub converted: usize,
+    pub failed: usize,
+    // 每个贴图的转换结果
+    pub textures: Vec<TextureResult>,
+    // 使用外部 RePKG 提取时的原始输出
+    pub repkg_output: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TextureResult {
+    pub path: String,
+    pub format: Option<TexFormat>,
+    // 转换成功时生成的图像路径
+    pub output: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct ExtractProgress {
+    pub input: String,
+    // 当前正在提取的 PKG 文件，tex 模式下为空
+    pub package: String,
+    pub entries_done: usize,
+    pub entries_total: usize,
+    pub bytes_written: u64,
+    pub current_file: String,
+}
+
+// 取消提取时返回的错误信息
+pub const CANCELLED: &str = "提取已取消";
+
+// 单次提取的运行状态：汇报进度、响应取消，并记录新建的文件以便取消时清理
+struct ExtractContext<'a> {
+    progress: ExtractProgress,
+    on_progress: &'a mut dyn FnMut(&ExtractProgress),
+    cancel: &'a AtomicBool,
+    // 本次提取新建的文件和目录；被覆盖的已有文件不在其中，取消时不会删除
+    created: Vec<PathBuf>,
+}
+
+impl ExtractContext<'_> {
+    // 每处理完一个条目（或贴图）就通过回调汇报一次进度
+    fn advance(&mut self, current_file: &str) {
+        self.progress.entries_done += 1;
+        self.progress.current_file = current_file.to_string();
+        (self.on_progress)(&self.progress);
+    }
+
+    fn check_cancelled(&self) -> Result<(), String> {
+        if self.cancel.load(Ordering::Relaxed) {
+            return Err(CANCELLED.to_string());
+        }
+        Ok(())
+    }
+
+    fn create_dir_all(&mut self, dir: &Path) -> Result<(), String> {
+        // 只记录最外层新建的目录，清理时整体删除
+        if let Some(top) = dir.ancestors().take_while(|d| !d.as_os_str().is_empty() && !d.exists()).last() {
+            self.created.push(top.to_path_buf());
+        }
+        fs::create_dir_all(dir).map_err(|e| format!("无法创建目录 {}: {}", dir.display(), e))
+    }
+
+    fn write_file(&mut self, path: &Path, data: &[u8]) -> Result<(), String> {
+        if !path.exists() {
+            self.created.push(path.to_path_buf());
+        }
+        fs::write(path, data).map_err(|e| format!("无法写入文件 {}: {}", path.display(), e))?;
+        self.progress.bytes_written += data.len() as u64;
+        Ok(())
+    }
+
+    fn copy_file(&mut self, from: &Path, to: &Path) -> Result<(), String> {
+        if !to.exists() {
+            self.created.push(to.to_path_buf());
+        }
+        let bytes = fs::copy(from, to).map_err(|e| format!("无法复制项目文件 {}: {}", from.display(), e))?;
+        self.progress.bytes_written += bytes;
+        Ok(())
+    }
+
+    // 按创建的逆序删除，已经不存在的路径直接忽略
+    fn cleanup(&mut self) {
+        for path in self.created.drain(..).rev() {
+            if path.is_dir() {
+                let _ = fs::remove_dir_all(&path);
+            } else {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+// 原生提取入口，输入可以是单个 PKG 文件，也可以是包含 PKG 的目录
+// cancel 被置位后会在下一个条目前停止，并删除本次提取新建的文件
+pub fn extract(
+    input: &Path,
+    options: &ExtractOptions,
+    cancel: &AtomicBool,
+    on_progress: &mut dyn FnMut(&ExtractProgress),
+) -> Result<ExtractSummary, String> {
+    let mut summary = ExtractSummary::default();
+    let mut context = ExtractContext {
+        progress: ExtractProgress {
+            input: input.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+        on_progress,
+        cancel,
+        created: Vec::new(),
+    };
+
+    let result = run_extract(input, options, &mut summary, &mut context);
+    if context.cancel.load(Ordering::Relaxed) {
+        context.cleanup();
+        return Err(CANCELLED.to_string());
+    }
+
+    result.map(|_| summary)
+}
+
+fn run_extract(
+    input: &Path,
+    options: &ExtractOptions,
+    summary: &mut ExtractSummary,
+    context: &mut ExtractContext,
+) -> Result<(), String> {
+    let output = PathBuf::from(options.output.as_deref().unwrap_or(DEFAULT_OUTPUT));
+
+    if input.is_file() && entry_extension(&input.to_string_lossy()) == "tex" {
+        context.progress.entries_total = 1;
+        return convert_tex_files(&[input.to_path_buf()], &output, options, summary, context);
+    }
+
+    if input.is_dir() && options.tex {
+        // tex 模式：把目录中的 .tex 文件直接转换为图像
+        let mut textures = Vec::new();
+        collect_files(input, "tex", options.recursive, &mut textures)?;
+        textures.sort();
+        context.progress.entries_total = textures.len();
+        return convert_tex_files(&textures, &output, options, summary, context);
+    }
+
+    let mut jobs = Vec::new();
+    if input.is_file() {
+        // 单个文件直接输出到目标目录，仅在 use_name 时追加项目名
+        let output = if options.use_name {
+            output.join(output_folder_name(input, true))
+        } else {
+            output
+        };
+        jobs.push((input.to_path_buf(), output));
+    } else if input.is_dir() {
+        let mut packages = Vec::new();
+        collect_files(input, "pkg", options.recursive, &mut packages)?;
+        packages.sort();
+
+        if packages.is_empty() {
+            return Err(format!("目录中没有找到PKG文件: {}", input.display()));
+        }
+
+        // 目录中的每个 PKG 输出到以文件夹名（或项目名）命名的子目录
+        for package in packages {
+            let package_output = output.join(output_folder_name(&package, options.use_name));
+            jobs.push((package, package_output));
+        }
+    } else {
+        return Err(format!("输入路径不存在: {}", input.display()));
+    }
+
+    // 先读取所有文件头统计条目总数，便于前端显示整体进度
+    for (pkg_path, _) in &jobs {
+        context.progress.entries_total += selected_entries(&pkg::read_package(pkg_path)?, options).len();
+    }
+
+    for (pkg_path, package_output) in &jobs {
+        extract_package(pkg_path, package_output, options, summary, context)?;
+    }
+
+    Ok(())
+}
+
+// 按 ignore_exts / only_exts 过滤需要提取的条目
+fn selected_entries(package: &Package, options: &ExtractOptions) -> Vec<PkgEntry> {
+    let ignore_exts = parse_extensions(options.ignore_exts.as_deref());
+    let only_exts = parse_extensions(options.only_exts.as_deref());
+
+    package
+        .entries
+        .iter()
+        .filter(|entry| {
+            let ext = entry_extension(&entry.path);
+            if !only_exts.is_empty() && !only_exts.contains(&ext) {
+                return false;
+            }
+            !ignore_exts.contains(&ext)
+        })
+        .cloned()
+        .collect()
+}
+
+fn extract_package(
+    pkg_path: &Path,
+    output: &Path,
+    options: &ExtractOptions,
+    summary: &mut ExtractSummary,
+    context: &mut ExtractContext,
+) -> Result<(), String> {
+    let mut reader = PkgReader::open(pkg_path)?;
+    let entries = selected_entries(reader.package(), options);
+    context.progress.package = pkg_path.to_string_lossy().to_string();
+
+    context.create_dir_all(output)?;
+
+    for entry in &entries {
+        context.check_cancelled()?;
+
+        let relative = sanitize_entry_path(&entry.path)?;
+        let target = if options.single_dir {
+            output.join(relative.file_name().unwrap_or_default())
+        } else {
+            output.join(&relative)
+        };
+
+        if target.exists() && !options.overwrite {
+            summary.skipped += 1;
+            context.advance(&entry.path);
+            continue;
+        }
+
+        if let Some(parent) = target.parent() {
+            context.create_dir_all(parent)?;
+        }
+
+        let data = reader.read_entry(entry)?;
+        context.write_file(&target, &data)?;
+        summary.extracted += 1;
+
+        if entry.entry_type == EntryType::Tex && !options.no_tex_convert {
+            write_converted_tex(&data, &target, options, summary, context)?;
+        }
+        context.advance(&entry.path);
+    }
+
+    // RePKG 在单目录模式下不复制项目文件，这里保持相同行为
+    if options.copy_project && !options.single_dir {
+        if let Some(project_dir) = pkg_path.parent() {
+            copy_project_files(project_dir, output, options.overwrite, context)?;
+        }
+    }
+
+    summary.packages += 1;
+    Ok(())
+}
+
+fn convert_tex_files(
+    textures: &[PathBuf],
+    output: &Path,
+    options: &ExtractOptions,
+    summary: &mut ExtractSummary,
+    context: &mut ExtractContext,
+) -> Result<(), String> {
+    context.create_dir_all(output)?;
+
+    for texture in textures {
+        context.check_cancelled()?;
+
+        let data = fs::read(texture).map_err(|e| format!("无法读取文件 {}: {}", texture.display(), e))?;
+        let target = output.join(texture.file_name().unwrap_or_default());
+        write_converted_tex(&data, &target, options, summary, context)?;
+        context.advance(&texture.to_string_lossy());
+    }
+
+    Ok(())
+}
+
+// 将 TEX 数据转换为图像写在 tex_path 旁边，转换失败只记录结果不中断提取
+fn write_converted_tex(
+    data: &[u8],
+    tex_path: &Path,
+    options: &ExtractOptions,
+    summary: &mut ExtractSummary,
+    context: &mut ExtractContext,
+) -> Result<(), String> {
+    let mut result = TextureResult {
+        path: tex_path.to_string_lossy().to_string(),
+        format: None,
+        output: None,
+        error: None,
+    };
+
+    let converted = tex::read_tex(data).and_then(|texture| {
+        result.format = Some(texture.header.format);
+
+        let embedded = tex::embedded_file(&texture)?;
+        let extension = match &embedded {
+            Some(file) => file.extension,
+            None => texture
+                .frame_info
+                .as_ref()
+                .and(options.animation_format.extension())
+                .unwrap_or("png"),
+        };
+        let target = tex_path.with_extension(extension);
+        if target.exists() && !options.overwrite {
+            return Ok(None);
+        }
+
+        // 嵌入的原始文件直接写出，保留原始画质与元数据
+        if let Some(file) = embedded {
+            return Ok(Some((target.clone(), vec![(target, file.data.into_owned())])));
+        }
+
+        convert_texture(&texture, tex_path, &target, options).map(|files| Some((target, files)))
+    });
+
+    match converted {
+        Ok(None) => {
+            summary.skipped += 1;
+            return Ok(());
+        }
+        Ok(Some((target, files))) => {
+            for (path, bytes) in files {
+                context.write_file(&path, &bytes)?;
+            }
+            result.output = Some(target.to_string_lossy().to_string());
+            summary.converted += 1;
+        }
+        Err(e) => {
+            eprintln!("警告：无法转换贴图 {}: {}", tex_path.display(), e);
+            result.error = Some(e);
+            summary.failed += 1;
+        }
+    }
+
+    summary.textures.push(result);
+    Ok(())
+}
+
+// 生成贴图的转换产物，第一个文件为主输出
+// 带帧信息的贴图按设置导出为动画，并可额外导出每一帧和完整的 mipmap 链
+fn convert_texture(
+    texture: &Tex,
+    tex_path: &Path,
+    target: &Path,
+    options: &ExtractOptions,
+) -> Result<Vec<(PathBuf, Vec<u8>)>, String> {
+    let mip_level = options.mip_level as usize;
+    let stem = tex_path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut files = Vec::new();
+
+    if texture.frame_info.is_none() || options.animation_format == AnimationFormat::None {
+        files.push((target.to_path_buf(), tex::convert_to_png(texture, mip_level)?));
+    }
+
+    if texture.frame_info.is_some() {
+        let frames = animation::extract_frames(texture)?;
+
+        if options.animation_format != AnimationFormat::None {
+            files.push((target.to_path_buf(), animation::encode(options.animation_format, &frames)?));
+        }
+
+        if options.export_frames {
+            for (index, frame) in frames.iter().enumerate() {
+                let frame_path = tex_path.with_file_name(format!("{}_frame_{:03}.png", stem, index));
+                files.push((frame_path, animation::encode_frame_png(frame)?));
+            }
+        }
+    }
+
+    if options.export_all_mips {
+        for level in 0..tex::mipmap_count(texture) {
+            let mip_path = tex_path.with_file_name(format!("{}_mip{}.png", stem, level));
+            files.push((mip_path, tex::convert_to_png(texture, level)?));
+        }
+    }
+
+    Ok(files)
+}
+
+pub fn collect_files(dir: &Path, extension: &str, recursive: bool, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("无法读取目录: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_file() {
+            if entry_extension(&path.to_string_lossy()) == extension {
+                files.push(path);
+            }
+        } else if path.is_dir() && recursive {
+            collect_files(&path, extension, recursive, files)?;
+        }
+    }
+
+    Ok(())
+}
+
+// 复制 project.json 和预览图到输出目录
+fn copy_project_files(
+    project_dir: &Path,
+    output: &Path,
+    overwrite: bool,
+    context: &mut ExtractContext,
+) -> Result<(), String> {
+    let entries = fs::read_dir(project_dir).map_err(|e| format!("无法读取目录: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        let file_name = entry.file_name().to_string_lossy().to_lowercase();
+        if file_name != "project.json" && !file_name.starts_with("preview.") {
+            continue;
+        }
+
+        let target = output.join(entry.file_name());
+        if target.exists() && !overwrite {
+            continue;
+        }
+
+        context.copy_file(&path, &target)?;
+    }
+
+    Ok(())
+}
+
+// 输出子目录名：优先使用 project.json 中的标题，否则使用 PKG 所在文件夹名
+fn output_folder_name(pkg_path: &Path, use_name: bool) -> String {
+    let folder_name = pkg_path
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+
+    if !use_name {
+        return folder_name;
+    }
+
+    pkg_path
+        .parent()
+        .map(|p| p.join("project.json"))
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|project| project.get("title").and_then(|t| t.as_str()).map(String::from))
+        .map(|title| {
+            title
+                .chars()
+                .map(|c| if "<>:\"/\\|?*".contains(c) || c.is_control() { '_' } else { c })
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .filter(|title| !title.is_empty())
+        .unwrap_or(folder_name)
+}
+
+// 拒绝绝对路径和 ".."，防止条目写到输出目录之外
+fn sanitize_entry_path(entry_path: &str) -> Result<PathBuf, String> {
+    let normalized = entry_path.replace('\\', "/");
+    let mut relative = PathBuf::new();
+
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            _ => return Err(format!("PKG条目路径不合法: {}", entry_path)),
+        }
+    }
+
+    if relative.as_os_str().is_empty() {
+        return Err(format!("PKG条目路径不合法: {}", entry_path));
+    }
+
+    Ok(relative)
+}
+
+// 解析逗号分隔的扩展名列表，统一为不带点的小写形式
+fn parse_extensions(exts: Option<&str>) -> Vec<String> {
+    exts.unwrap_or_default()
+        .split(',')
+        .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect()
+}
+
+fn entry_extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
@@ -0,0 +1,148 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::extract::collect_files;
+use crate::pkg::{EntryType, PkgReader};
+use crate::repkg::InfoOptions;
+use crate::tex::{self, TexInfo};
+
+#[derive(Serialize)]
+pub struct PkgInfo {
+    pub path: String,
+    pub magic: String,
+    pub version: Option<u32>,
+    pub entry_count: usize,
+    // 仅在 print_entries 或 tex 选项开启时填充
+    pub entries: Vec<PkgEntryInfo>,
+    // project.json 中 project_info 选项请求的字段
+    pub project: Option<Map<String, Value>>,
+}
+
+#[derive(Serialize)]
+pub struct PkgEntryInfo {
+    pub path: String,
+    pub size: u32,
+    pub offset: u32,
+    pub entry_type: EntryType,
+    // 仅在 tex 选项开启时解析
+    pub tex: Option<TexInfo>,
+    // 贴图解析失败的原因
+    pub tex_error: Option<String>,
+}
+
+// 读取单个 PKG 或目录中所有 PKG 的信息
+pub fn info(input: &Path, options: &InfoOptions) -> Result<Vec<PkgInfo>, String> {
+    let mut packages = Vec::new();
+    if input.is_file() {
+        packages.push(input.to_path_buf());
+    } else if input.is_dir() {
+        collect_files(input, "pkg", true, &mut packages)?;
+        packages.sort();
+    } else {
+        return Err(format!("输入路径不存在: {}", input.display()));
+    }
+
+    let title_filter = options.title_filter.as_deref().map(str::to_lowercase);
+    let mut infos = Vec::new();
+
+    for package in packages {
+        let project = read_project(&package);
+
+        if let Some(filter) = &title_filter {
+            let title = project
+                .as_ref()
+                .and_then(|p| p.get("title"))
+                .and_then(Value::as_str)
+                .map(str::to_lowercase);
+            if !title.is_some_and(|title| title.contains(filter.as_str())) {
+                continue;
+            }
+        }
+
+        infos.push(package_info(&package, project, options)?);
+    }
+
+    Ok(infos)
+}
+
+fn package_info(pkg_path: &Path, project: Option<Map<String, Value>>, options: &InfoOptions) -> Result<PkgInfo, String> {
+    let mut reader = PkgReader::open(pkg_path)?;
+    let package = reader.package().clone();
+
+    let mut entries = Vec::new();
+    if options.print_entries || options.tex {
+        for entry in &package.entries {
+            let mut info = PkgEntryInfo {
+                path: entry.path.clone(),
+                size: entry.length,
+                offset: entry.offset,
+                entry_type: entry.entry_type,
+                tex: None,
+                tex_error: None,
+            };
+
+            if options.tex && entry.entry_type == EntryType::Tex {
+                match reader.read_entry(entry).and_then(|data| tex::read_tex(&data)).and_then(|t| tex::info(&t)) {
+                    Ok(tex_info) => info.tex = Some(tex_info),
+                    Err(e) => info.tex_error = Some(e),
+                }
+            }
+
+            entries.push(info);
+        }
+    }
+
+    if options.sort {
+        sort_entries(&mut entries, options.sort_by.as_deref().unwrap_or("name"))?;
+    }
+
+    Ok(PkgInfo {
+        path: pkg_path.to_string_lossy().to_string(),
+        magic: package.magic,
+        version: package.version,
+        entry_count: package.entries.len(),
+        entries,
+        project: options
+            .project_info
+            .as_deref()
+            .and_then(|keys| project.map(|project| select_project_keys(project, keys))),
+    })
+}
+
+fn sort_entries(entries: &mut [PkgEntryInfo], sort_by: &str) -> Result<(), String> {
+    match sort_by.to_lowercase().as_str() {
+        "name" => entries.sort_by(|a, b| a.path.cmp(&b.path)),
+        "extension" => entries.sort_by(|a, b| {
+            let ext_a = Path::new(&a.path).extension().unwrap_or_default();
+            let ext_b = Path::new(&b.path).extension().unwrap_or_default();
+            ext_a.cmp(ext_b).then_with(|| a.path.cmp(&b.path))
+        }),
+        "size" => entries.sort_by(|a, b| a.size.cmp(&b.size).then_with(|| a.path.cmp(&b.path))),
+        other => return Err(format!("不支持的排序方式: {}", other)),
+    }
+
+    Ok(())
+}
+
+// keys 为逗号分隔的字段名，"*" 表示全部字段
+fn select_project_keys(project: Map<String, Value>, keys: &str) -> Map<String, Value> {
+    if keys.trim() == "*" {
+        return project;
+    }
+
+    let keys: Vec<&str> = keys.split(',').map(str::trim).filter(|k| !k.is_empty()).collect();
+    project
+        .into_iter()
+        .filter(|(key, _)| keys.iter().any(|k| k.eq_ignore_ascii_case(key)))
+        .collect()
+}
+
+fn read_project(pkg_path: &Path) -> Option<Map<String, Value>> {
+    let content = fs::read_to_string(pkg_path.parent()?.join("project.json")).ok()?;
+    match serde_json::from_str(&content).ok()? {
+        Value::Object(project) => Some(project),
+        _ => None,
+    }
+}
@@ -0,0 +1,428 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::error::AppError;
+use crate::extract::{ExtractProgress, ExtractSummary};
+use crate::process;
+use crate::repkg::{self, ExtractOptions};
+
+// 提取进度与结束事件，payload 中都带有 job_id
+pub const EXTRACT_PROGRESS_EVENT: &str = "extract-progress";
+pub const EXTRACT_FINISHED_EVENT: &str = "extract-finished";
+// 队列发生变化时推送完整的任务列表
+pub const EXTRACT_JOBS_EVENT: &str = "extract-jobs-changed";
+
+// 队列保存在应用数据目录中的文件名
+const QUEUE_FILE: &str = "extract_jobs.json";
+const DEFAULT_CONCURRENCY: usize = 2;
+
+// 后台提取任务的控制句柄
+#[derive(Clone, Default)]
+pub struct JobHandle {
+    pub cancel: Arc<AtomicBool>,
+    // 使用 RePKG 提取时正在运行的子进程
+    pub child: Arc<Mutex<Option<Child>>>,
+}
+
+impl JobHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    // 原生提取会在下一个条目前停止，RePKG 则连同其子进程一起结束
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        if let Some(child) = self.child.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            process::kill_tree(child);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExtractJob {
+    pub id: String,
+    pub input: String,
+    pub options: ExtractOptions,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub summary: Option<ExtractSummary>,
+    pub error: Option<AppError>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueueState {
+    concurrency: usize,
+    jobs: Vec<ExtractJob>,
+    // 正在运行的任务，不写入文件
+    #[serde(skip)]
+    handles: HashMap<String, JobHandle>,
+}
+
+// 持久化的提取任务队列，由 Tauri 作为全局状态管理
+pub struct ExtractQueue {
+    path: PathBuf,
+    next_id: AtomicU64,
+    state: Mutex<QueueState>,
+}
+
+impl ExtractQueue {
+    // 从应用数据目录读取队列，上次退出时仍在运行的任务重新排队
+    pub fn load(app: &AppHandle) -> Result<Self, AppError> {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|_| AppError::DirectoryUnavailable {
+                name: "app_data".to_string(),
+            })?;
+        fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+        let path = dir.join(QUEUE_FILE);
+
+        let mut state = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<QueueState>(&content).ok())
+            .unwrap_or(QueueState {
+                concurrency: DEFAULT_CONCURRENCY,
+                jobs: Vec::new(),
+                handles: HashMap::new(),
+            });
+
+        for job in &mut state.jobs {
+            if job.status == JobStatus::Running {
+                job.status = JobStatus::Pending;
+            }
+        }
+
+        Ok(ExtractQueue {
+            path,
+            next_id: AtomicU64::new(0),
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn enqueue(&self, app: &AppHandle, input: String, options: ExtractOptions) -> Result<String, AppError> {
+        let id = format!(
+            "{}-{}",
+            Utc::now().timestamp_millis(),
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+
+        self.update(app, |state| {
+            state.jobs.push(ExtractJob {
+                id: id.clone(),
+                input,
+                options,
+                status: JobStatus::Pending,
+                created_at: Utc::now(),
+                finished_at: None,
+                summary: None,
+                error: None,
+            });
+            Ok(())
+        })?;
+
+        Ok(id)
+    }
+
+    pub fn jobs(&self) -> Vec<ExtractJob> {
+        self.lock().jobs.clone()
+    }
+
+    pub fn set_concurrency(&self, app: &AppHandle, concurrency: usize) -> Result<(), AppError> {
+        if concurrency == 0 {
+            return Err(AppError::InvalidInput {
+                message: "并发数至少为 1".to_string(),
+            });
+        }
+        self.update(app, |state| {
+            state.concurrency = concurrency;
+            Ok(())
+        })
+    }
+
+    // 把任务移动到队列中的指定位置，超出范围时放到末尾
+    pub fn reorder(&self, app: &AppHandle, job_id: &str, position: usize) -> Result<(), AppError> {
+        self.update(app, |state| {
+            let index = find_job(state, job_id)?;
+            let job = state.jobs.remove(index);
+            let position = position.min(state.jobs.len());
+            state.jobs.insert(position, job);
+            Ok(())
+        })
+    }
+
+    // 暂停排队中的任务；运行中的任务会被中止并清理，恢复后重新开始
+    pub fn pause(&self, app: &AppHandle, job_id: &str) -> Result<(), AppError> {
+        self.update(app, |state| {
+            let index = find_job(state, job_id)?;
+            match state.jobs[index].status {
+                JobStatus::Pending => {}
+                JobStatus::Running => {
+                    if let Some(handle) = state.handles.get(job_id) {
+                        handle.cancel();
+                    }
+                }
+                status => return Err(invalid_state(job_id, status)),
+            }
+            state.jobs[index].status = JobStatus::Paused;
+            Ok(())
+        })
+    }
+
+    pub fn resume(&self, app: &AppHandle, job_id: &str) -> Result<(), AppError> {
+        self.requeue(app, job_id, &[JobStatus::Paused])
+    }
+
+    pub fn retry(&self, app: &AppHandle, job_id: &str) -> Result<(), AppError> {
+        self.requeue(app, job_id, &[JobStatus::Failed, JobStatus::Cancelled])
+    }
+
+    pub fn cancel(&self, app: &AppHandle, job_id: &str) -> Result<(), AppError> {
+        self.update(app, |state| {
+            let index = find_job(state, job_id)?;
+            match state.jobs[index].status {
+                JobStatus::Pending | JobStatus::Paused => {
+                    state.jobs[index].status = JobStatus::Cancelled;
+                    state.jobs[index].finished_at = Some(Utc::now());
+                }
+                // 运行中的任务由工作线程在结束时标记为已取消
+                JobStatus::Running => {
+                    if let Some(handle) = state.handles.get(job_id) {
+                        handle.cancel();
+                    }
+                }
+                status => return Err(invalid_state(job_id, status)),
+            }
+            Ok(())
+        })
+    }
+
+    // 从列表中移除已完成、失败或取消的任务
+    pub fn clear_finished(&self, app: &AppHandle) -> Result<(), AppError> {
+        self.update(app, |state| {
+            state.jobs.retain(|job| {
+                matches!(job.status, JobStatus::Pending | JobStatus::Running | JobStatus::Paused)
+            });
+            Ok(())
+        })
+    }
+
+    fn requeue(&self, app: &AppHandle, job_id: &str, from: &[JobStatus]) -> Result<(), AppError> {
+        self.update(app, |state| {
+            let index = find_job(state, job_id)?;
+            // 被中止的任务要等工作线程退出后才能重新排队
+            let job = &mut state.jobs[index];
+            if state.handles.contains_key(job_id) || !from.contains(&job.status) {
+                return Err(invalid_state(job_id, job.status));
+            }
+            job.status = JobStatus::Pending;
+            job.finished_at = None;
+            job.summary = None;
+            job.error = None;
+            Ok(())
+        })
+    }
+
+    // 修改队列后保存到文件、通知前端，并尝试启动新的任务
+    fn update(
+        &self,
+        app: &AppHandle,
+        change: impl FnOnce(&mut QueueState) -> Result<(), AppError>,
+    ) -> Result<(), AppError> {
+        {
+            let mut state = self.lock();
+            change(&mut state)?;
+            self.save(&state);
+            let _ = app.emit(EXTRACT_JOBS_EVENT, &state.jobs);
+        }
+        pump(app);
+        Ok(())
+    }
+
+    fn save(&self, state: &QueueState) {
+        let result = serde_json::to_string_pretty(state)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(&self.path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("警告：无法保存提取队列 {}: {}", self.path.display(), e);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn find_job(state: &QueueState, job_id: &str) -> Result<usize, AppError> {
+    state
+        .jobs
+        .iter()
+        .position(|job| job.id == job_id)
+        .ok_or_else(|| AppError::JobNotFound {
+            job_id: job_id.to_string(),
+        })
+}
+
+fn invalid_state(job_id: &str, status: JobStatus) -> AppError {
+    AppError::InvalidJobState {
+        job_id: job_id.to_string(),
+        status,
+    }
+}
+
+// 在并发数允许的范围内，按队列顺序启动等待中的任务
+pub fn pump(app: &AppHandle) {
+    let queue = app.state::<ExtractQueue>();
+    let mut state = queue.lock();
+
+    let mut started = false;
+    while state.handles.len() < state.concurrency {
+        let Some(job) = state.jobs.iter_mut().find(|job| job.status == JobStatus::Pending) else {
+            break;
+        };
+        job.status = JobStatus::Running;
+        let job = job.clone();
+
+        let handle = JobHandle::default();
+        state.handles.insert(job.id.clone(), handle.clone());
+        started = true;
+
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || run_job(app, job, handle));
+    }
+
+    if started {
+        queue.save(&state);
+        let _ = app.emit(EXTRACT_JOBS_EVENT, &state.jobs);
+    }
+}
+
+fn run_job(app: AppHandle, job: ExtractJob, handle: JobHandle) {
+    let result = repkg::run_extraction(&app, &job.id, &handle, &job.input, &job.options);
+    let cancelled = handle.is_cancelled();
+
+    let queue = app.state::<ExtractQueue>();
+    let _ = queue.update(&app, |state| {
+        state.handles.remove(&job.id);
+        let Some(entry) = state.jobs.iter_mut().find(|entry| entry.id == job.id) else {
+            return Ok(());
+        };
+
+        // 被暂停的任务保持暂停状态，等待恢复后重新提取
+        if entry.status == JobStatus::Paused {
+            return Ok(());
+        }
+
+        entry.finished_at = Some(Utc::now());
+        entry.status = match (&result, cancelled) {
+            (_, true) => JobStatus::Cancelled,
+            (Ok(_), false) => JobStatus::Completed,
+            (Err(_), false) => JobStatus::Failed,
+        };
+        match &result {
+            Ok(summary) => entry.summary = Some(summary.clone()),
+            Err(error) => entry.error = Some(error.clone()),
+        }
+        Ok(())
+    });
+
+    let _ = app.emit(
+        EXTRACT_FINISHED_EVENT,
+        ExtractFinishedEvent::new(job.id, cancelled, result),
+    );
+}
+
+#[derive(Serialize, Clone)]
+pub struct ExtractProgressEvent<'a> {
+    pub job_id: &'a str,
+    #[serde(flatten)]
+    pub progress: &'a ExtractProgress,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ExtractFinishedEvent {
+    pub job_id: String,
+    pub cancelled: bool,
+    pub summary: Option<ExtractSummary>,
+    pub error: Option<AppError>,
+}
+
+impl ExtractFinishedEvent {
+    pub fn new(job_id: String, cancelled: bool, result: Result<ExtractSummary, AppError>) -> Self {
+        let (summary, error) = match result {
+            Ok(summary) => (Some(summary), None),
+            Err(error) => (None, Some(error)),
+        };
+        ExtractFinishedEvent {
+            job_id,
+            cancelled,
+            summary,
+            error,
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn list_extraction_jobs(queue: State<'_, ExtractQueue>) -> Result<Vec<ExtractJob>, AppError> {
+    Ok(queue.jobs())
+}
+
+// position 为任务在队列中的新下标
+#[tauri::command]
+pub async fn reorder_extraction_job(
+    app: AppHandle,
+    queue: State<'_, ExtractQueue>,
+    job_id: String,
+    position: usize,
+) -> Result<(), AppError> {
+    queue.reorder(&app, &job_id, position)
+}
+
+#[tauri::command]
+pub async fn pause_extraction_job(app: AppHandle, queue: State<'_, ExtractQueue>, job_id: String) -> Result<(), AppError> {
+    queue.pause(&app, &job_id)
+}
+
+#[tauri::command]
+pub async fn resume_extraction_job(app: AppHandle, queue: State<'_, ExtractQueue>, job_id: String) -> Result<(), AppError> {
+    queue.resume(&app, &job_id)
+}
+
+// 重新执行失败或已取消的任务
+#[tauri::command]
+pub async fn retry_extraction_job(app: AppHandle, queue: State<'_, ExtractQueue>, job_id: String) -> Result<(), AppError> {
+    queue.retry(&app, &job_id)
+}
+
+#[tauri::command]
+pub async fn clear_finished_extraction_jobs(app: AppHandle, queue: State<'_, ExtractQueue>) -> Result<(), AppError> {
+    queue.clear_finished(&app)
+}
+
+// 同时运行的提取任务数量，修改后立即生效
+#[tauri::command]
+pub async fn set_extraction_concurrency(
+    app: AppHandle,
+    queue: State<'_, ExtractQueue>,
+    concurrency: usize,
+) -> Result<(), AppError> {
+    queue.set_concurrency(&app, concurrency)
+}
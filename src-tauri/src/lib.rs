@@ -1,12 +1,33 @@
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
+use tauri::Manager;
 use tauri_plugin_opener::OpenerExt;
 
+// 统一的命令错误类型
+mod error;
+use error::AppError;
+
+// 引入 PKG 解析与原生提取模块
+mod animation;
+mod dxt;
+mod extract;
+mod info;
+mod jobs;
+use jobs::{
+    clear_finished_extraction_jobs, list_extraction_jobs, pause_extraction_job, reorder_extraction_job,
+    resume_extraction_job, retry_extraction_job, set_extraction_concurrency,
+};
+mod pkg;
+mod process;
+mod tex;
+
 // 引入 RePKG 模块
 mod repkg;
-use repkg::{extract_pkg, get_file_info, info_pkg};
+mod repkg_locator;
+use repkg_locator::{get_repkg_status, set_repkg_path};
+use repkg::{cancel_extraction, extract_pkg, get_file_info, info_pkg, list_pkg_entries, repack_pkg};
 
 // 引入壁纸编辑器模块
 mod wallpaper_editor;
@@ -18,14 +39,6 @@ use tauri_plugin_dialog::DialogExt;
 // 从 repkg 模块导入 FileInfo 结构体
 use repkg::FileInfo;
 
-#[derive(Serialize, Deserialize)]
-struct WallpaperData {
-    id: String,
-    name: String,
-    image: String,
-    path: String,
-}
-
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct BackgroundFileInfo {
@@ -41,22 +54,22 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-async fn get_home_dir() -> Result<String, String> {
+async fn get_home_dir() -> Result<String, AppError> {
     let home_dir = dirs::home_dir()
-        .ok_or("无法获取用户主目录")?
+        .ok_or(AppError::DirectoryUnavailable { name: "home".to_string() })?
         .to_string_lossy()
         .to_string();
     Ok(home_dir)
 }
 
 #[tauri::command]
-async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, String> {
+async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
     let path = app.dialog().file().blocking_pick_folder();
     Ok(path.map(|p| p.to_string()))
 }
 
 #[tauri::command]
-async fn select_background_file(app: tauri::AppHandle) -> Result<Option<String>, String> {
+async fn select_background_file(app: tauri::AppHandle) -> Result<Option<String>, AppError> {
     let path = app
         .dialog()
         .file()
@@ -67,16 +80,16 @@ async fn select_background_file(app: tauri::AppHandle) -> Result<Option<String>,
 }
 
 #[tauri::command]
-async fn read_workshop_directory(path: String) -> Result<Vec<FileInfo>, String> {
+async fn read_workshop_directory(path: String) -> Result<Vec<FileInfo>, AppError> {
     let mut folders = Vec::new();
 
     match fs::read_dir(&path) {
         Ok(entries) => {
             for entry in entries.flatten() {
-                let metadata = entry.metadata().map_err(|e| e.to_string())?;
+                let metadata = entry.metadata().map_err(|e| AppError::io(entry.path(), e))?;
                 if metadata.is_dir() {
-                    let file_info = fs::metadata(&entry.path()).map_err(|e| e.to_string())?;
-                    let modified = file_info.modified().map_err(|e| e.to_string())?;
+                    let file_info = fs::metadata(entry.path()).map_err(|e| AppError::io(entry.path(), e))?;
+                    let modified = file_info.modified().map_err(|e| AppError::io(entry.path(), e))?;
                     let modified = chrono::DateTime::<chrono::Utc>::from(modified);
 
                     folders.push(FileInfo {
@@ -89,31 +102,31 @@ async fn read_workshop_directory(path: String) -> Result<Vec<FileInfo>, String>
             }
             Ok(folders)
         }
-        Err(e) => Err(format!("无法读取目录: {}", e)),
+        Err(e) => Err(AppError::io(&path, e)),
     }
 }
 
 #[tauri::command]
-async fn read_json_file(path: String) -> Result<serde_json::Value, String> {
-    let content = fs::read_to_string(&path).map_err(|e| format!("无法读取文件: {}", e))?;
-    serde_json::from_str(&content).map_err(|e| format!("JSON解析错误: {}", e))
+async fn read_json_file(path: String) -> Result<serde_json::Value, AppError> {
+    let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+    serde_json::from_str(&content).map_err(|e| AppError::json(&path, e))
 }
 
 #[tauri::command]
-async fn check_file_exists(path: String) -> Result<bool, String> {
+async fn check_file_exists(path: String) -> Result<bool, AppError> {
     Ok(Path::new(&path).exists())
 }
 
 #[tauri::command]
-async fn read_directory_files(path: String) -> Result<Vec<FileInfo>, String> {
+async fn read_directory_files(path: String) -> Result<Vec<FileInfo>, AppError> {
     let mut files = Vec::new();
 
     match fs::read_dir(&path) {
         Ok(entries) => {
             for entry in entries.flatten() {
-                let metadata = entry.metadata().map_err(|e| e.to_string())?;
-                let file_info = fs::metadata(&entry.path()).map_err(|e| e.to_string())?;
-                let modified = file_info.modified().map_err(|e| e.to_string())?;
+                let metadata = entry.metadata().map_err(|e| AppError::io(entry.path(), e))?;
+                let file_info = fs::metadata(entry.path()).map_err(|e| AppError::io(entry.path(), e))?;
+                let modified = file_info.modified().map_err(|e| AppError::io(entry.path(), e))?;
                 let modified = chrono::DateTime::<chrono::Utc>::from(modified);
 
                 files.push(FileInfo {
@@ -125,47 +138,47 @@ async fn read_directory_files(path: String) -> Result<Vec<FileInfo>, String> {
             }
             Ok(files)
         }
-        Err(e) => Err(format!("无法读取目录: {}", e)),
+        Err(e) => Err(AppError::io(&path, e)),
     }
 }
 
 #[tauri::command]
-async fn read_image_as_base64(path: String) -> Result<String, String> {
+async fn read_image_as_base64(path: String) -> Result<String, AppError> {
     use base64::Engine;
 
-    let image_data = fs::read(&path).map_err(|e| format!("无法读取图片文件: {}", e))?;
+    let image_data = fs::read(&path).map_err(|e| AppError::io(&path, e))?;
 
     Ok(base64::engine::general_purpose::STANDARD.encode(image_data))
 }
 
 #[tauri::command]
-async fn file_exists(path: String) -> Result<bool, String> {
+async fn file_exists(path: String) -> Result<bool, AppError> {
     Ok(Path::new(&path).exists())
 }
 
 #[tauri::command]
-async fn read_text_file(path: String) -> Result<String, String> {
-    fs::read_to_string(&path).map_err(|e| format!("无法读取文件: {}", e))
+async fn read_text_file(path: String) -> Result<String, AppError> {
+    fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))
 }
 
 #[tauri::command]
-async fn write_text_file(path: String, contents: String) -> Result<(), String> {
-    fs::write(&path, contents).map_err(|e| format!("无法写入文件: {}", e))
+async fn write_text_file(path: String, contents: String) -> Result<(), AppError> {
+    fs::write(&path, contents).map_err(|e| AppError::io(&path, e))
 }
 
 #[tauri::command]
-async fn open_folder(path: String) -> Result<(), String> {
+async fn open_folder(path: String) -> Result<(), AppError> {
     #[cfg(target_os = "windows")]
     {
         // 确保路径存在
         if !Path::new(&path).exists() {
-            return Err(format!("路径不存在: {}", path));
+            return Err(AppError::not_found(&path));
         }
 
         Command::new("explorer")
             .arg(&path)
             .spawn()
-            .map_err(|e| format!("无法打开文件夹: {}", e))?;
+            .map_err(AppError::system)?;
     }
 
     #[cfg(target_os = "macos")]
@@ -173,7 +186,7 @@ async fn open_folder(path: String) -> Result<(), String> {
         Command::new("open")
             .arg(&path)
             .spawn()
-            .map_err(|e| format!("无法打开文件夹: {}", e))?;
+            .map_err(AppError::system)?;
     }
 
     #[cfg(target_os = "linux")]
@@ -181,54 +194,54 @@ async fn open_folder(path: String) -> Result<(), String> {
         Command::new("xdg-open")
             .arg(&path)
             .spawn()
-            .map_err(|e| format!("无法打开文件夹: {}", e))?;
+            .map_err(AppError::system)?;
     }
 
     Ok(())
 }
 
 #[tauri::command]
-async fn minimize_window(window: tauri::Window) -> Result<(), String> {
-    window.minimize().map_err(|e| e.to_string())
+async fn minimize_window(window: tauri::Window) -> Result<(), AppError> {
+    window.minimize().map_err(AppError::system)
 }
 
 #[tauri::command]
-async fn maximize_window(window: tauri::Window) -> Result<(), String> {
-    window.maximize().map_err(|e| e.to_string())
+async fn maximize_window(window: tauri::Window) -> Result<(), AppError> {
+    window.maximize().map_err(AppError::system)
 }
 
 #[tauri::command]
-async fn unmaximize_window(window: tauri::Window) -> Result<(), String> {
-    window.unmaximize().map_err(|e| e.to_string())
+async fn unmaximize_window(window: tauri::Window) -> Result<(), AppError> {
+    window.unmaximize().map_err(AppError::system)
 }
 
 #[tauri::command]
-async fn close_window(window: tauri::Window) -> Result<(), String> {
-    window.close().map_err(|e| e.to_string())
+async fn close_window(window: tauri::Window) -> Result<(), AppError> {
+    window.close().map_err(AppError::system)
 }
 
 #[tauri::command]
-async fn open_shell(app: tauri::AppHandle, path: String) -> Result<(), String> {
+async fn open_shell(app: tauri::AppHandle, path: String) -> Result<(), AppError> {
     app.opener()
         .open_url(&path, None::<&str>)
-        .map_err(|e| format!("无法打开链接: {}", e))
+        .map_err(AppError::system)
 }
 
 #[tauri::command]
-async fn is_window_maximized(window: tauri::Window) -> Result<bool, String> {
-    window.is_maximized().map_err(|e| e.to_string())
+async fn is_window_maximized(window: tauri::Window) -> Result<bool, AppError> {
+    window.is_maximized().map_err(AppError::system)
 }
 
 #[tauri::command]
-async fn create_directory(path: String) -> Result<(), String> {
+async fn create_directory(path: String) -> Result<(), AppError> {
     let path = Path::new(&path);
-    fs::create_dir_all(path).map_err(|e| format!("无法创建目录 {}: {}", path.display(), e))
+    fs::create_dir_all(path).map_err(|e| AppError::io(path, e))
 }
 
 #[tauri::command]
 async fn cleanup_directory_before_extract(
     path: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let path = Path::new(&path);
     if !path.exists() {
         return Ok(()); // 如果目录不存在，无需清理
@@ -236,7 +249,7 @@ async fn cleanup_directory_before_extract(
     
     // 只删除根目录下的文件，不删除子目录中的文件
     // 这样可以确保在为每个壁纸单独生成文件夹时，不会影响其他壁纸文件夹
-    let entries = fs::read_dir(path).map_err(|e| e.to_string())?;
+    let entries = fs::read_dir(path).map_err(|e| AppError::io(path, e))?;
     
     for entry in entries.flatten() {
         let file_path = entry.path();
@@ -258,10 +271,10 @@ async fn cleanup_directory_before_extract(
 async fn cleanup_non_media_files(
     path: String,
     allowed_extensions: Vec<String>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let path = Path::new(&path);
     if !path.exists() {
-        return Err("路径不存在".to_string());
+        return Err(AppError::not_found(path));
     }
 
     let allowed_extensions: Vec<String> = allowed_extensions
@@ -275,8 +288,8 @@ async fn cleanup_non_media_files(
         })
         .collect();
 
-    fn cleanup_dir(dir_path: &Path, allowed: &[String]) -> Result<(), String> {
-        let entries = fs::read_dir(dir_path).map_err(|e| e.to_string())?;
+    fn cleanup_dir(dir_path: &Path, allowed: &[String]) -> Result<(), AppError> {
+        let entries = fs::read_dir(dir_path).map_err(|e| AppError::io(dir_path, e))?;
 
         for entry in entries.flatten() {
             let path = entry.path();
@@ -284,19 +297,19 @@ async fn cleanup_non_media_files(
                 if let Some(ext) = path.extension() {
                     let ext_str = format!(".{}", ext.to_string_lossy().to_lowercase());
                     if !allowed.contains(&ext_str) {
-                        fs::remove_file(&path).map_err(|e| e.to_string())?;
+                        fs::remove_file(&path).map_err(|e| AppError::io(&path, e))?;
                     }
                 } else {
                     // 没有扩展名的文件也删除
-                    fs::remove_file(&path).map_err(|e| e.to_string())?;
+                    fs::remove_file(&path).map_err(|e| AppError::io(&path, e))?;
                 }
             } else if path.is_dir() {
                 // 递归处理子目录
                 cleanup_dir(&path, allowed)?;
 
                 // 如果目录为空，删除目录
-                if fs::read_dir(&path).map_err(|e| e.to_string())?.count() == 0 {
-                    fs::remove_dir(&path).map_err(|e| e.to_string())?;
+                if fs::read_dir(&path).map_err(|e| AppError::io(&path, e))?.count() == 0 {
+                    fs::remove_dir(&path).map_err(|e| AppError::io(&path, e))?;
                 }
             }
         }
@@ -309,34 +322,36 @@ async fn cleanup_non_media_files(
 }
 
 #[tauri::command]
-async fn get_background_dir() -> Result<String, String> {
+async fn get_background_dir() -> Result<String, AppError> {
     let background_dir = dirs::data_dir()
-        .ok_or("无法获取数据目录")?
+        .ok_or(AppError::DirectoryUnavailable { name: "data".to_string() })?
         .join("repkg-gui")
         .join("backgrounds");
     
     // 确保目录存在
-    std::fs::create_dir_all(&background_dir).map_err(|e| format!("无法创建背景目录: {}", e))?;
+    std::fs::create_dir_all(&background_dir).map_err(|e| AppError::io(&background_dir, e))?;
     
     Ok(background_dir.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-async fn copy_background_file(source_path: String) -> Result<BackgroundFileInfo, String> {
+async fn copy_background_file(source_path: String) -> Result<BackgroundFileInfo, AppError> {
     let source = Path::new(&source_path);
     if !source.exists() {
-        return Err("背景文件不存在".to_string());
+        return Err(AppError::not_found(source));
     }
 
     if !source.is_file() {
-        return Err("选择的背景资源不是文件".to_string());
+        return Err(AppError::InvalidInput {
+            message: format!("选择的背景资源不是文件: {}", source_path),
+        });
     }
 
     let background_dir = dirs::data_dir()
-        .ok_or("无法获取数据目录")?
+        .ok_or(AppError::DirectoryUnavailable { name: "data".to_string() })?
         .join("repkg-gui")
         .join("backgrounds");
-    fs::create_dir_all(&background_dir).map_err(|e| format!("无法创建背景目录: {}", e))?;
+    fs::create_dir_all(&background_dir).map_err(|e| AppError::io(&background_dir, e))?;
 
     let file_stem = source
         .file_stem()
@@ -361,7 +376,7 @@ async fn copy_background_file(source_path: String) -> Result<BackgroundFileInfo,
 
     let unique_suffix = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
-        .map_err(|e| format!("无法生成文件名: {}", e))?
+        .map_err(AppError::system)?
         .as_millis();
 
     let target_file_name = if extension.is_empty() {
@@ -371,9 +386,9 @@ async fn copy_background_file(source_path: String) -> Result<BackgroundFileInfo,
     };
 
     let target_path = background_dir.join(&target_file_name);
-    fs::copy(source, &target_path).map_err(|e| format!("无法复制背景文件: {}", e))?;
+    fs::copy(source, &target_path).map_err(|e| AppError::io(&target_path, e))?;
 
-    let metadata = fs::metadata(&target_path).map_err(|e| format!("无法读取背景文件信息: {}", e))?;
+    let metadata = fs::metadata(&target_path).map_err(|e| AppError::io(&target_path, e))?;
 
     Ok(BackgroundFileInfo {
         file_path: target_path.to_string_lossy().to_string(),
@@ -387,9 +402,9 @@ async fn copy_background_file(source_path: String) -> Result<BackgroundFileInfo,
 }
 
 #[tauri::command]
-async fn cleanup_background_files(keep_file_path: Option<String>) -> Result<(), String> {
+async fn cleanup_background_files(keep_file_path: Option<String>) -> Result<(), AppError> {
     let background_dir = dirs::data_dir()
-        .ok_or("无法获取数据目录")?
+        .ok_or(AppError::DirectoryUnavailable { name: "data".to_string() })?
         .join("repkg-gui")
         .join("backgrounds");
 
@@ -405,8 +420,8 @@ async fn cleanup_background_files(keep_file_path: Option<String>) -> Result<(),
         "jpg", "jpeg", "png", "gif", "webp", "mp4", "webm", "mov", "avi",
     ];
 
-    for entry in fs::read_dir(&background_dir).map_err(|e| format!("无法读取背景目录: {}", e))? {
-        let entry = entry.map_err(|e| format!("无法读取背景目录项: {}", e))?;
+    for entry in fs::read_dir(&background_dir).map_err(|e| AppError::io(&background_dir, e))? {
+        let entry = entry.map_err(|e| AppError::io(&background_dir, e))?;
         let path = entry.path();
 
         if !path.is_file() {
@@ -426,7 +441,7 @@ async fn cleanup_background_files(keep_file_path: Option<String>) -> Result<(),
             .unwrap_or_default();
 
         if allowed_extensions.contains(&extension.as_str()) {
-            fs::remove_file(&path).map_err(|e| format!("无法删除旧背景文件: {}", e))?;
+            fs::remove_file(&path).map_err(|e| AppError::io(&path, e))?;
         }
     }
 
@@ -439,7 +454,7 @@ async fn write_background_chunk(
     chunk_data: Vec<u8>,
     chunk_index: usize,
     is_last_chunk: bool
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     use std::fs;
     use std::io::Write;
     
@@ -447,31 +462,31 @@ async fn write_background_chunk(
     
     // 如果是第一个块，创建新文件；否则追加到现有文件
     let mut file = if chunk_index == 0 {
-        fs::File::create(path).map_err(|e| format!("无法创建文件: {}", e))?
+        fs::File::create(path).map_err(|e| AppError::io(path, e))?
     } else {
         fs::OpenOptions::new()
             .append(true)
             .open(path)
-            .map_err(|e| format!("无法打开文件进行追加: {}", e))?
+            .map_err(|e| AppError::io(path, e))?
     };
     
     // 写入数据块
-    file.write_all(&chunk_data).map_err(|e| format!("无法写入文件块: {}", e))?;
+    file.write_all(&chunk_data).map_err(|e| AppError::io(path, e))?;
     
     // 如果是最后一个块，确保文件被正确关闭
     if is_last_chunk {
-        file.sync_all().map_err(|e| format!("无法同步文件: {}", e))?;
+        file.sync_all().map_err(|e| AppError::io(path, e))?;
     }
     
     Ok(())
 }
 
 #[tauri::command]
-async fn get_background_file_path(file_name: String) -> Result<String, String> {
+async fn get_background_file_path(file_name: String) -> Result<String, AppError> {
     
     // 构建背景文件路径
     let background_dir = dirs::data_dir()
-        .ok_or("无法获取数据目录")?
+        .ok_or(AppError::DirectoryUnavailable { name: "data".to_string() })?
         .join("repkg-gui")
         .join("backgrounds");
     
@@ -479,7 +494,7 @@ async fn get_background_file_path(file_name: String) -> Result<String, String> {
     
     // 检查文件是否存在
     if !file_path.exists() {
-        return Err("背景文件不存在".to_string());
+        return Err(AppError::not_found(&file_path));
     }
     
     // 返回文件路径
@@ -491,10 +506,10 @@ async fn flatten_media_files(
     path: String,
     allowed_extensions: Vec<String>,
     overwrite: bool,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let path = Path::new(&path);
     if !path.exists() {
-        return Err("路径不存在".to_string());
+        return Err(AppError::not_found(path));
     }
 
     let allowed_extensions: Vec<String> = allowed_extensions
@@ -515,8 +530,8 @@ async fn flatten_media_files(
         dir_path: &Path,
         allowed: &[String],
         files: &mut Vec<std::path::PathBuf>,
-    ) -> Result<(), String> {
-        let entries = fs::read_dir(dir_path).map_err(|e| e.to_string())?;
+    ) -> Result<(), AppError> {
+        let entries = fs::read_dir(dir_path).map_err(|e| AppError::io(dir_path, e))?;
 
         for entry in entries.flatten() {
             let path = entry.path();
@@ -550,8 +565,7 @@ async fn flatten_media_files(
             let final_target_path = if target_path.exists() {
                 if overwrite {
                     // 如果启用覆盖，先删除现有文件
-                    fs::remove_file(&target_path)
-                        .map_err(|e| format!("无法删除现有文件 {}: {}", target_path.display(), e))?;
+                    fs::remove_file(&target_path).map_err(|e| AppError::io(&target_path, e))?;
                     target_path
                 } else {
                     // 如果未启用覆盖，添加数字后缀
@@ -605,8 +619,8 @@ async fn flatten_media_files(
     }
 
     // 删除所有子目录
-    fn remove_all_subdirs(dir_path: &Path) -> Result<(), String> {
-        let entries = fs::read_dir(dir_path).map_err(|e| e.to_string())?;
+    fn remove_all_subdirs(dir_path: &Path) -> Result<(), AppError> {
+        let entries = fs::read_dir(dir_path).map_err(|e| AppError::io(dir_path, e))?;
         let mut removed_dirs = 0;
         let mut failed_dirs = 0;
 
@@ -634,14 +648,14 @@ async fn flatten_media_files(
     }
     
     // 检查子目录中是否有残留文件
-    fn check_remaining_files(dir_path: &Path) -> Result<bool, String> {
-        let entries = fs::read_dir(dir_path).map_err(|e| e.to_string())?;
+    fn check_remaining_files(dir_path: &Path) -> Result<bool, AppError> {
+        let entries = fs::read_dir(dir_path).map_err(|e| AppError::io(dir_path, e))?;
         
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_dir() {
                 // 检查子目录中是否还有文件
-                let sub_entries = fs::read_dir(&path).map_err(|e| e.to_string())?;
+                let sub_entries = fs::read_dir(&path).map_err(|e| AppError::io(&path, e))?;
                 for sub_entry in sub_entries.flatten() {
                     let sub_path = sub_entry.path();
                     if sub_path.is_file() {
@@ -680,6 +694,14 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
+        .setup(|app| {
+            // 恢复上次未完成的提取任务
+            let queue = jobs::ExtractQueue::load(app.handle())?;
+            app.manage(queue);
+            app.manage(repkg_locator::RepkgLocator::load(app.handle())?);
+            jobs::pump(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             get_home_dir,
@@ -702,11 +724,23 @@ pub fn run() {
             is_window_maximized,
             create_directory,
             extract_pkg,
+            cancel_extraction,
+            list_extraction_jobs,
+            reorder_extraction_job,
+            pause_extraction_job,
+            resume_extraction_job,
+            retry_extraction_job,
+            clear_finished_extraction_jobs,
+            set_extraction_concurrency,
             info_pkg,
+            list_pkg_entries,
+            repack_pkg,
             cleanup_directory_before_extract,
             cleanup_non_media_files,
             flatten_media_files,
             get_file_info,
+            get_repkg_status,
+            set_repkg_path,
             import_to_wallpaper_editor,
             get_steamapps_paths,
             find_workshop_path_from_extract_path,
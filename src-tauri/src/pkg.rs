@@ -0,0 +1,310 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+// scene.pkg 文件头 magic 的前缀，完整形式如 "PKGV0001"
+const MAGIC_PREFIX: &str = "PKGV";
+// 没有参考 PKG 时写入的文件头
+const DEFAULT_MAGIC: &str = "PKGV0001";
+// 转换后的贴图扩展名，与同名 .tex 并存时视为提取产物
+const CONVERTED_TEX_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "mp4"];
+// 长度前缀字符串的上限，防止损坏的文件导致超大内存分配
+const MAX_MAGIC_LENGTH: u32 = 32;
+const MAX_PATH_LENGTH: u32 = 4096;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    Tex,
+    Binary,
+}
+
+impl EntryType {
+    fn from_path(path: &str) -> Self {
+        if Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("tex"))
+        {
+            EntryType::Tex
+        } else {
+            EntryType::Binary
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PkgEntry {
+    pub path: String,
+    // 相对于条目数据区起始位置的偏移
+    pub offset: u32,
+    pub length: u32,
+    pub entry_type: EntryType,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Package {
+    pub magic: String,
+    // 从 magic 中解析出的版本号，例如 "PKGV0019" -> 19
+    pub version: Option<u32>,
+    // 文件头（magic + 条目表）的字节数，条目数据紧随其后
+    pub header_size: u64,
+    pub entries: Vec<PkgEntry>,
+}
+
+pub struct PkgReader<R> {
+    reader: R,
+    package: Package,
+}
+
+impl PkgReader<BufReader<File>> {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("无法打开PKG文件: {}", e))?;
+        PkgReader::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> PkgReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, String> {
+        let package = read_header(&mut reader)?;
+
+        // 校验每个条目都落在文件范围内
+        let file_len = reader
+            .seek(SeekFrom::End(0))
+            .map_err(|e| format!("无法读取PKG文件: {}", e))?;
+        let data_len = file_len.saturating_sub(package.header_size);
+        for entry in &package.entries {
+            if entry.offset as u64 + entry.length as u64 > data_len {
+                return Err(format!("PKG条目超出文件范围: {}", entry.path));
+            }
+        }
+
+        Ok(PkgReader { reader, package })
+    }
+
+    pub fn package(&self) -> &Package {
+        &self.package
+    }
+
+    pub fn into_package(self) -> Package {
+        self.package
+    }
+
+    pub fn read_entry(&mut self, entry: &PkgEntry) -> Result<Vec<u8>, String> {
+        self.reader
+            .seek(SeekFrom::Start(self.package.header_size + entry.offset as u64))
+            .map_err(|e| format!("无法定位PKG条目 {}: {}", entry.path, e))?;
+
+        let mut data = vec![0u8; entry.length as usize];
+        self.reader
+            .read_exact(&mut data)
+            .map_err(|e| format!("无法读取PKG条目 {}: {}", entry.path, e))?;
+        Ok(data)
+    }
+}
+
+// 解析 PKG 文件头，返回条目表
+pub fn read_package(path: &Path) -> Result<Package, String> {
+    PkgReader::open(path).map(PkgReader::into_package)
+}
+
+// 将目录打包为 PKG 文件
+// 条目按参考 PKG 的顺序排列，参考中不存在的文件按路径排序追加在后面，
+// 因此对提取出的目录配合原始 PKG 重新打包可以得到逐字节相同的结果
+pub fn write_package(input_dir: &Path, output: &Path, reference: Option<&Package>) -> Result<Package, String> {
+    if !input_dir.is_dir() {
+        return Err(format!("输入目录不存在: {}", input_dir.display()));
+    }
+
+    let mut files = BTreeSet::new();
+    collect_pack_files(input_dir, input_dir, output, &mut files)?;
+    skip_converted_textures(&mut files);
+
+    let mut ordered = Vec::new();
+    if let Some(reference) = reference {
+        for entry in &reference.entries {
+            let path = entry.path.replace('\\', "/");
+            if files.remove(&path) {
+                ordered.push(path);
+            }
+        }
+    }
+    ordered.extend(files);
+
+    let magic = reference.map_or(DEFAULT_MAGIC, |r| r.magic.as_str()).to_string();
+    let mut entries = Vec::new();
+    let mut offset: u64 = 0;
+
+    for path in ordered {
+        let length = fs::metadata(input_dir.join(&path))
+            .map_err(|e| format!("无法获取文件信息 {}: {}", path, e))?
+            .len();
+        if offset + length > u32::MAX as u64 {
+            return Err("打包内容超过PKG格式支持的4GB上限".to_string());
+        }
+
+        entries.push(PkgEntry {
+            entry_type: EntryType::from_path(&path),
+            offset: offset as u32,
+            length: length as u32,
+            path,
+        });
+        offset += length;
+    }
+
+    // 先写入临时文件再替换，允许输出路径与参考 PKG 相同
+    let temp_output = output.with_extension("pkg.tmp");
+    let header_size = write_package_file(&temp_output, input_dir, &magic, &entries).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_output);
+    })?;
+    fs::rename(&temp_output, output).map_err(|e| format!("无法写入PKG文件 {}: {}", output.display(), e))?;
+
+    Ok(Package {
+        version: magic.strip_prefix(MAGIC_PREFIX).and_then(|v| v.parse().ok()),
+        magic,
+        header_size,
+        entries,
+    })
+}
+
+fn write_package_file(path: &Path, input_dir: &Path, magic: &str, entries: &[PkgEntry]) -> Result<u64, String> {
+    let file = File::create(path).map_err(|e| format!("无法创建PKG文件 {}: {}", path.display(), e))?;
+    let mut writer = BufWriter::new(file);
+    let map_write_err = |e: std::io::Error| format!("无法写入PKG文件: {}", e);
+
+    write_sized_string(&mut writer, magic).map_err(map_write_err)?;
+    writer
+        .write_all(&(entries.len() as u32).to_le_bytes())
+        .map_err(map_write_err)?;
+    for entry in entries {
+        write_sized_string(&mut writer, &entry.path).map_err(map_write_err)?;
+        writer.write_all(&entry.offset.to_le_bytes()).map_err(map_write_err)?;
+        writer.write_all(&entry.length.to_le_bytes()).map_err(map_write_err)?;
+    }
+    let header_size = writer.stream_position().map_err(map_write_err)?;
+
+    for entry in entries {
+        let mut source = File::open(input_dir.join(&entry.path))
+            .map_err(|e| format!("无法读取文件 {}: {}", entry.path, e))?;
+        let copied = std::io::copy(&mut source, &mut writer).map_err(map_write_err)?;
+        if copied != entry.length as u64 {
+            return Err(format!("文件在打包过程中被修改: {}", entry.path));
+        }
+    }
+
+    writer.flush().map_err(map_write_err)?;
+    Ok(header_size)
+}
+
+// 收集需要打包的文件（使用 "/" 分隔的相对路径）
+// 根目录下的 project.json、预览图和其他 PKG 文件不属于 PKG 内容
+fn collect_pack_files(root: &Path, dir: &Path, output: &Path, files: &mut BTreeSet<String>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("无法读取目录: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_pack_files(root, &path, output, files)?;
+            continue;
+        }
+
+        if path == output || path == output.with_extension("pkg.tmp") {
+            continue;
+        }
+
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if dir == root {
+            let name = relative.to_lowercase();
+            if name == "project.json" || name.starts_with("preview.") || name.ends_with(".pkg") {
+                continue;
+            }
+        }
+
+        files.insert(relative);
+    }
+
+    Ok(())
+}
+
+// 跳过与 .tex 同名的图片，它们是提取时转换出的文件
+fn skip_converted_textures(files: &mut BTreeSet<String>) {
+    let textures: Vec<String> = files
+        .iter()
+        .filter(|path| EntryType::from_path(path) == EntryType::Tex)
+        .map(|path| path[..path.len() - ".tex".len()].to_string())
+        .collect();
+
+    for stem in textures {
+        for ext in CONVERTED_TEX_EXTENSIONS {
+            files.remove(&format!("{}.{}", stem, ext));
+        }
+    }
+}
+
+fn write_sized_string<W: Write>(writer: &mut W, value: &str) -> std::io::Result<()> {
+    writer.write_all(&(value.len() as u32).to_le_bytes())?;
+    writer.write_all(value.as_bytes())
+}
+
+fn read_header<R: Read + Seek>(reader: &mut R) -> Result<Package, String> {
+    let magic = read_sized_string(reader, MAX_MAGIC_LENGTH)?;
+    if !magic.starts_with(MAGIC_PREFIX) {
+        return Err(format!("不是有效的PKG文件，未知的文件头: {}", magic));
+    }
+
+    let entry_count = read_u32(reader)?;
+    let mut entries = Vec::new();
+
+    for _ in 0..entry_count {
+        let path = read_sized_string(reader, MAX_PATH_LENGTH)?;
+        let offset = read_u32(reader)?;
+        let length = read_u32(reader)?;
+
+        entries.push(PkgEntry {
+            entry_type: EntryType::from_path(&path),
+            path,
+            offset,
+            length,
+        });
+    }
+
+    let header_size = reader
+        .stream_position()
+        .map_err(|e| format!("无法读取PKG文件头: {}", e))?;
+
+    Ok(Package {
+        version: magic.strip_prefix(MAGIC_PREFIX).and_then(|v| v.parse().ok()),
+        magic,
+        header_size,
+        entries,
+    })
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, String> {
+    let mut buf = [0u8; 4];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|e| format!("PKG文件头已损坏: {}", e))?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_sized_string<R: Read>(reader: &mut R, max_length: u32) -> Result<String, String> {
+    let length = read_u32(reader)?;
+    if length > max_length {
+        return Err(format!("PKG文件头已损坏: 字符串长度 {} 超出限制", length));
+    }
+
+    let mut buf = vec![0u8; length as usize];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|e| format!("PKG文件头已损坏: {}", e))?;
+    String::from_utf8(buf).map_err(|e| format!("PKG文件头已损坏: {}", e))
+}
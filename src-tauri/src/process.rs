@@ -0,0 +1,96 @@
+use std::io::Read;
+use std::process::{Child, Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 创建外部命令：Windows 上隐藏终端窗口，其他平台放入独立的进程组，便于结束整个进程树
+pub fn command(program: &str) -> Command {
+    let mut command = Command::new(program);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    command
+}
+
+// 结束子进程及其创建的所有子进程
+pub fn kill_tree(child: &mut Child) {
+    let pid = child.id().to_string();
+
+    #[cfg(target_os = "windows")]
+    let _ = command("taskkill").args(["/PID", &pid, "/T", "/F"]).status();
+
+    // 子进程是进程组的组长，向负的 pid 发送信号即结束整个进程组
+    #[cfg(unix)]
+    let _ = Command::new("kill").args(["-KILL", &format!("-{}", pid)]).status();
+
+    let _ = child.kill();
+}
+
+// 运行命令并收集输出，超时后结束进程树并返回 None
+// 只适合输出很少的命令，输出超过管道缓冲区时会一直等到超时
+pub fn output_with_timeout(mut command: Command, timeout: Duration) -> Option<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().ok()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            kill_tree(&mut child);
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    if let Some(mut pipe) = child.stdout.take() {
+        let _ = pipe.read_to_end(&mut stdout);
+    }
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_end(&mut stderr);
+    }
+
+    Some(Output { status, stdout, stderr })
+}
+
+// 读取管道直到结束，最多保留 limit 字节，超出部分直接丢弃
+// 返回读取到的内容以及是否发生了截断
+pub fn read_capped(mut reader: impl Read, limit: usize) -> (Vec<u8>, bool) {
+    let mut captured = Vec::new();
+    let mut truncated = false;
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let remaining = limit.saturating_sub(captured.len());
+                if n > remaining {
+                    truncated = true;
+                }
+                captured.extend_from_slice(&buffer[..n.min(remaining)]);
+            }
+        }
+    }
+
+    (captured, truncated)
+}
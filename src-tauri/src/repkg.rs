@@ -2,9 +2,31 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
-use std::process::Command;
-
-#[derive(Serialize, Deserialize)]
+use std::io::{BufRead, BufReader};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::animation::AnimationFormat;
+use crate::error::AppError;
+use crate::extract::{self, ExtractProgress, ExtractSummary};
+use crate::info::{self, PkgInfo};
+use crate::jobs::{ExtractProgressEvent, ExtractQueue, JobHandle, EXTRACT_PROGRESS_EVENT};
+use crate::pkg::{self, Package};
+use crate::process;
+use crate::repkg_locator::RepkgLocator;
+
+// 外部 RePKG 单次运行的默认超时时间
+const DEFAULT_REPKG_TIMEOUT_SECS: u64 = 600;
+// stdout 和 stderr 各自最多保留的字节数
+const MAX_CAPTURED_OUTPUT: usize = 1024 * 1024;
+const TRUNCATED_NOTICE: &str = "\n...(输出过长，已截断)";
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct ExtractOptions {
     pub output: Option<String>,
     pub ignore_exts: Option<String>,
@@ -17,6 +39,24 @@ pub struct ExtractOptions {
     pub use_name: bool,
     pub no_tex_convert: bool,
     pub overwrite: bool,
+    // 为 true 时改用外部 RePKG 程序提取，默认使用内置的原生实现
+    #[serde(default)]
+    pub use_repkg: bool,
+    // 动画贴图的导出格式，默认导出 GIF
+    #[serde(default)]
+    pub animation_format: AnimationFormat,
+    // 同时把动画的每一帧导出为单独的 PNG
+    #[serde(default)]
+    pub export_frames: bool,
+    // 转换贴图时导出的 mipmap 层级，0 为原始尺寸
+    #[serde(default)]
+    pub mip_level: u32,
+    // 额外把每一级 mipmap 导出为单独的 PNG
+    #[serde(default)]
+    pub export_all_mips: bool,
+    // 外部 RePKG 的超时时间（秒），为空时使用默认值
+    #[serde(default)]
+    pub repkg_timeout_secs: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -29,9 +69,49 @@ pub struct InfoOptions {
     pub title_filter: Option<String>,
 }
 
+// 把提取任务加入队列并立即返回任务 ID
+// 进度通过 extract-progress 事件推送，结束时发送 extract-finished 事件
+#[tauri::command]
+pub async fn extract_pkg(
+    app: AppHandle,
+    queue: State<'_, ExtractQueue>,
+    input: String,
+    options: ExtractOptions,
+) -> Result<String, AppError> {
+    queue.enqueue(&app, input, options)
+}
+
+// 取消排队中或正在运行的提取任务，已写出的部分文件会被清理
 #[tauri::command]
-pub async fn extract_pkg(input: String, options: ExtractOptions) -> Result<String, String> {
-    let repkg_path = get_repkg_path();
+pub async fn cancel_extraction(app: AppHandle, queue: State<'_, ExtractQueue>, job_id: String) -> Result<(), AppError> {
+    queue.cancel(&app, &job_id)
+}
+
+pub fn run_extraction(
+    app: &AppHandle,
+    job_id: &str,
+    handle: &JobHandle,
+    input: &str,
+    options: &ExtractOptions,
+) -> Result<ExtractSummary, AppError> {
+    let mut emit_progress = |progress: &ExtractProgress| {
+        let _ = app.emit(EXTRACT_PROGRESS_EVENT, ExtractProgressEvent { job_id, progress });
+    };
+
+    if !options.use_repkg {
+        return extract::extract(Path::new(input), options, &handle.cancel, &mut emit_progress)
+            .map_err(AppError::package);
+    }
+
+    let repkg = app.state::<RepkgLocator>().resolve(app)?;
+    // 无法识别版本号时仍然尝试运行，只拒绝明确过旧的版本
+    if repkg.version.is_some() && !repkg.compatible {
+        return Err(AppError::RepkgIncompatible {
+            path: repkg.path,
+            version: repkg.version,
+        });
+    }
+    let repkg_path = repkg.path;
 
     let mut args = vec!["extract"];
 
@@ -82,7 +162,7 @@ pub async fn extract_pkg(input: String, options: ExtractOptions) -> Result<Strin
         args.push("--overwrite");
     }
 
-    args.push(&input);
+    args.push(input);
 
     // 添加调试日志
     println!("RePKG 路径: {}", repkg_path);
@@ -90,81 +170,57 @@ pub async fn extract_pkg(input: String, options: ExtractOptions) -> Result<Strin
     println!("输出路径: {:?}", options.output);
     println!("命令参数: {:?}", args);
 
-    run_repkg_command(&repkg_path, &args).await
-}
-
-#[tauri::command]
-pub async fn info_pkg(input: String, options: InfoOptions) -> Result<String, String> {
-    let repkg_path = get_repkg_path();
-
-    let mut args = vec!["info"];
-
-    if options.sort {
-        args.push("-s");
-    }
-
-    if let Some(sort_by) = &options.sort_by {
-        args.push("-b");
-        args.push(sort_by);
-    }
+    // RePKG 不会提前给出条目总数，这里只按输出行汇报当前处理的文件
+    let mut progress = ExtractProgress {
+        input: input.to_string(),
+        ..Default::default()
+    };
 
-    if options.tex {
-        args.push("-t");
-    }
+    // RePKG 写出了哪些文件无从得知，取消时只能删除本次新建的输出目录
+    let output_dir = Path::new(options.output.as_deref().unwrap_or("./output"));
+    let output_existed = output_dir.exists();
 
-    if let Some(project_info) = &options.project_info {
-        args.push("-p");
-        args.push(project_info);
-    }
+    let timeout = Duration::from_secs(options.repkg_timeout_secs.unwrap_or(DEFAULT_REPKG_TIMEOUT_SECS));
+    let result = run_repkg_command(&repkg_path, &args, timeout, handle, &mut |line| {
+        progress.entries_done += 1;
+        progress.current_file = line.to_string();
+        emit_progress(&progress);
+    });
 
-    if options.print_entries {
-        args.push("-e");
+    if handle.is_cancelled() {
+        if !output_existed {
+            let _ = fs::remove_dir_all(output_dir);
+        }
+        return Err(AppError::Cancelled);
     }
 
-    if let Some(title_filter) = &options.title_filter {
-        args.push("--title-filter");
-        args.push(title_filter);
-    }
+    let output = result?;
+    Ok(ExtractSummary {
+        repkg_output: Some(output),
+        ..Default::default()
+    })
+}
 
-    args.push(&input);
+#[tauri::command]
+pub async fn info_pkg(input: String, options: InfoOptions) -> Result<Vec<PkgInfo>, AppError> {
+    info::info(Path::new(&input), &options).map_err(AppError::package)
+}
 
-    run_repkg_command(&repkg_path, &args).await
+// 不依赖 RePKG，直接解析 PKG 文件头并返回条目表
+#[tauri::command]
+pub async fn list_pkg_entries(input: String) -> Result<Package, AppError> {
+    pkg::read_package(Path::new(&input)).map_err(AppError::package)
 }
 
-fn get_repkg_path() -> String {
-    // 根据操作系统选择正确的 RePKG 可执行文件
-    let exe_name = if cfg!(target_os = "windows") {
-        "RePKG.exe"
-    } else {
-        "RePKG"
+// 将目录重新打包为 PKG，reference 指向原始 PKG 时沿用其文件头与条目顺序
+#[tauri::command]
+pub async fn repack_pkg(input: String, output: String, reference: Option<String>) -> Result<Package, AppError> {
+    let reference = match reference {
+        Some(reference) => Some(pkg::read_package(Path::new(&reference)).map_err(AppError::package)?),
+        None => None,
     };
 
-    // 在开发环境中，RePKG.exe 位于项目的 bin 目录中
-    let mut dev_path = std::env::current_dir().unwrap_or_default();
-    dev_path.push("bin");
-    dev_path.push(exe_name);
-
-    // 如果在开发环境中找到，则使用该路径
-    if dev_path.exists() {
-        return dev_path.to_string_lossy().to_string();
-    }
-
-    // 在生产环境中，我们假设 RePKG.exe 与应用程序位于同一目录
-    // 或者在资源目录中
-    if let Ok(current_exe) = std::env::current_exe() {
-        if let Some(parent) = current_exe.parent() {
-            let mut prod_path = parent.to_path_buf();
-            prod_path.push(exe_name);
-
-            // 如果在可执行文件同目录找到，则使用该路径
-            if prod_path.exists() {
-                return prod_path.to_string_lossy().to_string();
-            }
-        }
-    }
-
-    // 如果都没有找到，返回默认路径
-    dev_path.to_string_lossy().to_string()
+    pkg::write_package(Path::new(&input), Path::new(&output), reference.as_ref()).map_err(AppError::package)
 }
 
 #[derive(Serialize, Deserialize)]
@@ -176,11 +232,9 @@ pub struct FileInfo {
 }
 
 #[tauri::command]
-pub fn get_file_info(path: String) -> Result<FileInfo, String> {
-    let metadata = fs::metadata(&path).map_err(|e| format!("无法获取文件信息: {}", e))?;
-    let modified = metadata
-        .modified()
-        .map_err(|e| format!("无法获取修改时间: {}", e))?;
+pub fn get_file_info(path: String) -> Result<FileInfo, AppError> {
+    let metadata = fs::metadata(&path).map_err(|e| AppError::io(&path, e))?;
+    let modified = metadata.modified().map_err(|e| AppError::io(&path, e))?;
     let modified = DateTime::<Utc>::from(modified);
     let path_buf = std::path::PathBuf::from(&path);
     let name = path_buf
@@ -197,40 +251,116 @@ pub fn get_file_info(path: String) -> Result<FileInfo, String> {
     })
 }
 
-async fn run_repkg_command(repkg_path: &str, args: &[&str]) -> Result<String, String> {
+// 逐行读取 RePKG 的标准输出，每读到一行非空输出就调用 on_line
+// 运行期间子进程保存在任务句柄中，供取消任务时结束进程；超过 timeout 时结束整个进程树
+fn run_repkg_command(
+    repkg_path: &str,
+    args: &[&str],
+    timeout: Duration,
+    handle: &JobHandle,
+    on_line: &mut dyn FnMut(&str),
+) -> Result<String, AppError> {
     // 检查 RePKG 可执行文件是否存在
     if !Path::new(repkg_path).exists() {
-        return Err(format!("RePKG Not Found: {}", repkg_path));
+        return Err(AppError::RepkgNotFound {
+            path: repkg_path.to_string(),
+        });
     }
 
     // 执行命令
-    let mut command = Command::new(repkg_path);
-    command.args(args);
-    
-    // 在 Windows 上隐藏终端窗口
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        command.creation_flags(CREATE_NO_WINDOW);
-    }
-    
-    let output = command
-        .output()
-        .map_err(|e| format!("执行 RePKG 命令失败: {}", e))?;
+    let mut child = process::command(repkg_path)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(AppError::system)?;
+
+    let stderr_pipe = child.stderr.take().ok_or_else(|| AppError::system("无法读取 RePKG 错误输出"))?;
+    let stdout_pipe = child.stdout.take().ok_or_else(|| AppError::system("无法读取 RePKG 输出"))?;
+    *handle.child.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+    // 子进程登记之前就收到取消请求时，在这里补上结束进程
+    if handle.is_cancelled() {
+        handle.cancel();
+    }
 
-    // 添加调试信息
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    // 看门狗线程：在超时前没有收到结束通知就结束进程树
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let watchdog = {
+        let child = handle.child.clone();
+        let timed_out = timed_out.clone();
+        thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = done_rx.recv_timeout(timeout) {
+                timed_out.store(true, Ordering::Relaxed);
+                if let Some(child) = child.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                    process::kill_tree(child);
+                }
+            }
+        })
+    };
+
+    // stderr 在单独的线程中读取，避免管道写满导致子进程阻塞
+    let stderr_reader = thread::spawn(move || process::read_capped(stderr_pipe, MAX_CAPTURED_OUTPUT));
+
+    // 超出上限的输出不再保存，但仍继续读取并汇报进度
+    let mut stdout = String::new();
+    let mut stdout_truncated = false;
+    for line in BufReader::new(stdout_pipe).split(b'\n') {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = String::from_utf8_lossy(&line);
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            on_line(trimmed);
+        }
+        if stdout.len() + line.len() < MAX_CAPTURED_OUTPUT {
+            stdout.push_str(&line);
+            stdout.push('\n');
+        } else {
+            stdout_truncated = true;
+        }
+    }
+
+    // 子进程被结束后管道关闭，读取循环随之退出
+    let mut child = handle
+        .child
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .ok_or_else(|| AppError::system("RePKG 进程已丢失"))?;
+    let status = child.wait().map_err(AppError::system)?;
+    drop(done_tx);
+    let _ = watchdog.join();
+
+    let (stderr, stderr_truncated) = stderr_reader.join().unwrap_or_default();
+    let mut stderr = String::from_utf8_lossy(&stderr).to_string();
+    if stdout_truncated {
+        stdout.push_str(TRUNCATED_NOTICE);
+    }
+    if stderr_truncated {
+        stderr.push_str(TRUNCATED_NOTICE);
+    }
 
+    // 添加调试信息
     println!("RePKG stdout: {}", stdout);
     println!("RePKG stderr: {}", stderr);
-    println!("RePKG exit code: {:?}", output.status.code());
+    println!("RePKG exit code: {:?}", status.code());
+
+    if timed_out.load(Ordering::Relaxed) {
+        return Err(AppError::RepkgTimeout {
+            timeout_secs: timeout.as_secs(),
+        });
+    }
 
     // 检查执行结果
-    if output.status.success() {
-        Ok(stdout.to_string())
+    if status.success() {
+        Ok(stdout)
     } else {
-        Err(format!("RePKG 命令执行失败: {}", stderr))
+        Err(AppError::RepkgFailed {
+            exit_code: status.code(),
+            stderr,
+        })
     }
 }
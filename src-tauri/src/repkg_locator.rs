@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::AppError;
+use crate::process;
+
+// 可通过环境变量指定 RePKG 的位置
+pub const REPKG_PATH_ENV: &str = "REPKG_PATH";
+// 保存用户配置的文件名，位于应用数据目录
+const CONFIG_FILE: &str = "repkg.json";
+// 本程序使用的 --no-tex-convert、--overwrite 等参数从 0.3.0 开始提供
+const MIN_VERSION: (u32, u32, u32) = (0, 3, 0);
+// 检测版本时等待 RePKG 退出的最长时间
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg(target_os = "windows")]
+const EXE_NAME: &str = "RePKG.exe";
+#[cfg(not(target_os = "windows"))]
+const EXE_NAME: &str = "RePKG";
+
+// RePKG 的来源，按查找优先级排列
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RepkgSource {
+    Config,
+    Env,
+    Path,
+    Resource,
+    // 开发环境下的 ./bin 目录
+    DevBin,
+    // 与程序位于同一目录
+    ExeDir,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RepkgBinary {
+    pub path: String,
+    pub source: RepkgSource,
+    // 无法识别版本号时为 None
+    pub version: Option<String>,
+    pub compatible: bool,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct RepkgConfig {
+    path: Option<String>,
+}
+
+// RePKG 定位器：保存用户配置，并缓存版本检测结果，避免每次提取都运行一次
+pub struct RepkgLocator {
+    config_path: PathBuf,
+    config: Mutex<RepkgConfig>,
+    detected: Mutex<Option<RepkgBinary>>,
+}
+
+impl RepkgLocator {
+    pub fn load(app: &AppHandle) -> Result<Self, AppError> {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|_| AppError::DirectoryUnavailable {
+                name: "app_data".to_string(),
+            })?;
+        let config_path = dir.join(CONFIG_FILE);
+        let config = fs::read_to_string(&config_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Ok(RepkgLocator {
+            config_path,
+            config: Mutex::new(config),
+            detected: Mutex::new(None),
+        })
+    }
+
+    // 按 用户配置 > 环境变量 > PATH > 资源目录 > ./bin > 程序目录 的顺序查找
+    pub fn resolve(&self, app: &AppHandle) -> Result<RepkgBinary, AppError> {
+        let (path, source) = self.find(app)?;
+
+        let mut detected = lock(&self.detected);
+        if let Some(binary) = detected.as_ref().filter(|b| Path::new(&b.path) == path) {
+            return Ok(binary.clone());
+        }
+
+        let version = detect_version(&path);
+        let binary = RepkgBinary {
+            path: path.to_string_lossy().to_string(),
+            source,
+            compatible: version.as_deref().and_then(parse_version).is_some_and(|v| v >= MIN_VERSION),
+            version,
+        };
+        *detected = Some(binary.clone());
+        Ok(binary)
+    }
+
+    pub fn set_path(&self, path: Option<String>) -> Result<(), AppError> {
+        let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+        if let Some(path) = &path {
+            if !Path::new(path).is_file() {
+                return Err(AppError::not_found(path));
+            }
+        }
+
+        let mut config = lock(&self.config);
+        config.path = path;
+        if let Some(dir) = self.config_path.parent() {
+            fs::create_dir_all(dir).map_err(|e| AppError::io(dir, e))?;
+        }
+        let content = serde_json::to_string_pretty(&*config).map_err(|e| AppError::json(&self.config_path, e))?;
+        fs::write(&self.config_path, content).map_err(|e| AppError::io(&self.config_path, e))?;
+        Ok(())
+    }
+
+    fn find(&self, app: &AppHandle) -> Result<(PathBuf, RepkgSource), AppError> {
+        // 明确配置的路径无效时直接报错，不悄悄换成其他位置的 RePKG
+        if let Some(path) = lock(&self.config).path.clone() {
+            let path = PathBuf::from(path);
+            return if path.is_file() {
+                Ok((path, RepkgSource::Config))
+            } else {
+                Err(AppError::RepkgNotFound {
+                    path: path.to_string_lossy().to_string(),
+                })
+            };
+        }
+
+        if let Some(path) = std::env::var_os(REPKG_PATH_ENV).filter(|p| !p.is_empty()) {
+            let path = PathBuf::from(path);
+            return if path.is_file() {
+                Ok((path, RepkgSource::Env))
+            } else {
+                Err(AppError::RepkgNotFound {
+                    path: path.to_string_lossy().to_string(),
+                })
+            };
+        }
+
+        let mut candidates = Vec::new();
+        if let Some(paths) = std::env::var_os("PATH") {
+            for dir in std::env::split_paths(&paths) {
+                candidates.push((dir.join(EXE_NAME), RepkgSource::Path));
+            }
+        }
+        if let Ok(resource_dir) = app.path().resource_dir() {
+            // 打包时 bin 目录作为资源随程序一起安装
+            candidates.push((resource_dir.join("bin").join(EXE_NAME), RepkgSource::Resource));
+            candidates.push((resource_dir.join(EXE_NAME), RepkgSource::Resource));
+        }
+        if let Ok(current_dir) = std::env::current_dir() {
+            candidates.push((current_dir.join("bin").join(EXE_NAME), RepkgSource::DevBin));
+        }
+        if let Some(exe_dir) = std::env::current_exe().ok().and_then(|p| p.parent().map(Path::to_path_buf)) {
+            candidates.push((exe_dir.join(EXE_NAME), RepkgSource::ExeDir));
+        }
+
+        candidates
+            .into_iter()
+            .find(|(path, _)| path.is_file())
+            .ok_or_else(|| AppError::RepkgNotFound {
+                path: EXE_NAME.to_string(),
+            })
+    }
+}
+
+// RePKG 使用 CommandLineParser，--version 会输出类似 "RePKG 0.4.0-alpha" 的一行
+fn detect_version(path: &Path) -> Option<String> {
+    let mut command = process::command(&path.to_string_lossy());
+    command.arg("--version");
+    let output = process::output_with_timeout(command, VERSION_TIMEOUT)?;
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    text.split_whitespace()
+        .map(|word| word.trim_start_matches(['v', 'V']))
+        .find(|word| parse_version(word).is_some())
+        .map(str::to_string)
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 报告当前使用的 RePKG 及其版本是否兼容
+#[tauri::command]
+pub async fn get_repkg_status(app: AppHandle, locator: State<'_, RepkgLocator>) -> Result<RepkgBinary, AppError> {
+    locator.resolve(&app)
+}
+
+// 设置 RePKG 路径，传入空值时恢复自动查找
+#[tauri::command]
+pub async fn set_repkg_path(
+    app: AppHandle,
+    locator: State<'_, RepkgLocator>,
+    path: Option<String>,
+) -> Result<RepkgBinary, AppError> {
+    locator.set_path(path)?;
+    locator.resolve(&app)
+}
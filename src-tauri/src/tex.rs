@@ -0,0 +1,551 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+use crate::dxt::{self, BlockFormat};
+
+// Wallpaper Engine .tex 文件中的字符串以 \0 结尾，长度不超过 16 字节
+const MAX_MAGIC_LENGTH: usize = 16;
+// TexFlags 中表示动画贴图（带 TEXS 帧信息）的标记位
+const FLAG_IS_GIF: u32 = 4;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TexFormat {
+    Rgba8888,
+    Dxt5,
+    Dxt3,
+    Dxt1,
+    Rg88,
+    R8,
+    Unknown(i32),
+}
+
+impl TexFormat {
+    fn from_raw(value: i32) -> Self {
+        match value {
+            0 => TexFormat::Rgba8888,
+            4 => TexFormat::Dxt5,
+            6 => TexFormat::Dxt3,
+            7 => TexFormat::Dxt1,
+            8 => TexFormat::Rg88,
+            9 => TexFormat::R8,
+            other => TexFormat::Unknown(other),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct TexHeader {
+    pub format: TexFormat,
+    pub flags: u32,
+    // 贴图尺寸（通常补齐为 2 的幂）
+    pub texture_width: u32,
+    pub texture_height: u32,
+    // 实际图像内容的尺寸
+    pub image_width: u32,
+    pub image_height: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct TexMipmap {
+    pub width: u32,
+    pub height: u32,
+    pub lz4_compressed: bool,
+    pub decompressed_size: u32,
+    pub data: Vec<u8>,
+}
+
+impl TexMipmap {
+    // 返回解压后的像素数据
+    pub fn pixels(&self) -> Result<Cow<'_, [u8]>, String> {
+        if !self.lz4_compressed {
+            return Ok(Cow::Borrowed(&self.data));
+        }
+
+        lz4_flex::block::decompress(&self.data, self.decompressed_size as usize)
+            .map(Cow::Owned)
+            .map_err(|e| format!("LZ4解压失败: {}", e))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TexImage {
+    pub mipmaps: Vec<TexMipmap>,
+}
+
+// 精灵图中的一帧，坐标为图集像素坐标
+// width/height 为 0 时帧是旋转存放的，此时尺寸由 height_x/width_y 给出
+#[derive(Serialize, Clone, Debug)]
+pub struct TexFrame {
+    pub image_id: u32,
+    // 帧持续时间，单位为秒
+    pub frametime: f32,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub width_y: f32,
+    pub height_x: f32,
+    pub height: f32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FrameInfo {
+    // TEXS0003 中记录的动画画布尺寸，旧版本为 0
+    pub gif_width: u32,
+    pub gif_height: u32,
+    pub frames: Vec<TexFrame>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tex {
+    pub header: TexHeader,
+    // FreeImage 格式编号，-1 表示原始像素数据
+    pub image_format: i32,
+    // TEXB0004 中标记贴图内容为 MP4 视频
+    pub is_video_mp4: bool,
+    pub images: Vec<TexImage>,
+    pub frame_info: Option<FrameInfo>,
+}
+
+// 提供给前端的贴图概要信息
+#[derive(Serialize, Clone, Debug)]
+pub struct TexInfo {
+    #[serde(flatten)]
+    pub header: TexHeader,
+    pub image_count: usize,
+    pub mipmap_count: usize,
+    // 嵌入文件的扩展名，贴图为原始像素数据时为空
+    pub embedded: Option<&'static str>,
+    // 动画贴图的帧数
+    pub frame_count: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorType {
+    Rgba,
+    GrayscaleAlpha,
+    Grayscale,
+}
+
+impl ColorType {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            ColorType::Rgba => 4,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Grayscale => 1,
+        }
+    }
+}
+
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub color: ColorType,
+    pub pixels: Vec<u8>,
+}
+
+impl DecodedImage {
+    // 转换为 RGBA8 像素
+    pub fn to_rgba(&self) -> Vec<u8> {
+        match self.color {
+            ColorType::Rgba => self.pixels.clone(),
+            ColorType::GrayscaleAlpha => self
+                .pixels
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            ColorType::Grayscale => self.pixels.iter().flat_map(|v| [*v, *v, *v, 255]).collect(),
+        }
+    }
+}
+
+pub fn read_tex(bytes: &[u8]) -> Result<Tex, String> {
+    let mut reader = ByteReader::new(bytes);
+
+    let magic = reader.read_nstring()?;
+    if magic != "TEXV0005" {
+        return Err(format!("不支持的TEX文件头: {}", magic));
+    }
+    let magic = reader.read_nstring()?;
+    if magic != "TEXI0001" {
+        return Err(format!("不支持的TEX信息块: {}", magic));
+    }
+
+    let header = TexHeader {
+        format: TexFormat::from_raw(reader.read_i32()?),
+        flags: reader.read_u32()?,
+        texture_width: reader.read_u32()?,
+        texture_height: reader.read_u32()?,
+        image_width: reader.read_u32()?,
+        image_height: reader.read_u32()?,
+    };
+    // 未知用途的保留字段
+    reader.read_u32()?;
+
+    let container_magic = reader.read_nstring()?;
+    let image_count = reader.read_u32()?;
+    let (image_format, is_video_mp4) = match container_magic.as_str() {
+        "TEXB0001" | "TEXB0002" => (-1, false),
+        "TEXB0003" => (reader.read_i32()?, false),
+        "TEXB0004" => (reader.read_i32()?, reader.read_u32()? == 1),
+        other => return Err(format!("不支持的TEX图像容器: {}", other)),
+    };
+
+    let mut images = Vec::new();
+    for _ in 0..image_count {
+        let mipmap_count = reader.read_u32()?;
+        let mut mipmaps = Vec::new();
+
+        for _ in 0..mipmap_count {
+            let mipmap = match container_magic.as_str() {
+                "TEXB0001" => read_mipmap_v1(&mut reader)?,
+                "TEXB0004" => read_mipmap_v4(&mut reader)?,
+                _ => read_mipmap_v2(&mut reader)?,
+            };
+            mipmaps.push(mipmap);
+        }
+
+        images.push(TexImage { mipmaps });
+    }
+
+    let frame_info = if header.flags & FLAG_IS_GIF != 0 {
+        Some(read_frame_info(&mut reader)?)
+    } else {
+        None
+    };
+
+    Ok(Tex {
+        header,
+        image_format,
+        is_video_mp4,
+        images,
+        frame_info,
+    })
+}
+
+fn read_frame_info(reader: &mut ByteReader) -> Result<FrameInfo, String> {
+    let magic = reader.read_nstring()?;
+    let frame_count = reader.read_u32()?;
+
+    let (gif_width, gif_height) = match magic.as_str() {
+        "TEXS0001" | "TEXS0002" => (0, 0),
+        "TEXS0003" => (reader.read_u32()?, reader.read_u32()?),
+        other => return Err(format!("不支持的TEX帧信息块: {}", other)),
+    };
+
+    let mut frames = Vec::new();
+    for _ in 0..frame_count {
+        let image_id = reader.read_u32()?;
+        let frametime = reader.read_f32()?;
+
+        // TEXS0001 中的坐标为整数，之后的版本为浮点数
+        let mut values = [0f32; 6];
+        for value in values.iter_mut() {
+            *value = if magic == "TEXS0001" {
+                reader.read_i32()? as f32
+            } else {
+                reader.read_f32()?
+            };
+        }
+
+        frames.push(TexFrame {
+            image_id,
+            frametime,
+            x: values[0],
+            y: values[1],
+            width: values[2],
+            width_y: values[3],
+            height_x: values[4],
+            height: values[5],
+        });
+    }
+
+    Ok(FrameInfo {
+        gif_width,
+        gif_height,
+        frames,
+    })
+}
+
+fn read_mipmap_v1(reader: &mut ByteReader) -> Result<TexMipmap, String> {
+    let width = reader.read_u32()?;
+    let height = reader.read_u32()?;
+    let data = reader.read_sized_bytes()?;
+
+    Ok(TexMipmap {
+        width,
+        height,
+        lz4_compressed: false,
+        decompressed_size: data.len() as u32,
+        data,
+    })
+}
+
+fn read_mipmap_v2(reader: &mut ByteReader) -> Result<TexMipmap, String> {
+    let width = reader.read_u32()?;
+    let height = reader.read_u32()?;
+    let lz4_compressed = reader.read_u32()? == 1;
+    let decompressed_size = reader.read_u32()?;
+    let data = reader.read_sized_bytes()?;
+
+    Ok(TexMipmap {
+        width,
+        height,
+        lz4_compressed,
+        decompressed_size,
+        data,
+    })
+}
+
+// TEXB0004 的每个 mipmap 前带有一段条件参数，跳过后与 V2 结构相同
+fn read_mipmap_v4(reader: &mut ByteReader) -> Result<TexMipmap, String> {
+    if reader.read_u32()? != 1 || reader.read_u32()? != 2 {
+        return Err("TEXB0004 mipmap 参数无效".to_string());
+    }
+    reader.read_nstring_max(usize::MAX)?;
+    if reader.read_u32()? != 1 {
+        return Err("TEXB0004 mipmap 参数无效".to_string());
+    }
+
+    read_mipmap_v2(reader)
+}
+
+// 解码第一张图像的指定 mipmap 层级，并裁剪到实际图像尺寸
+pub fn decode(tex: &Tex, level: usize) -> Result<DecodedImage, String> {
+    decode_image(tex, 0, level)
+}
+
+pub fn info(tex: &Tex) -> Result<TexInfo, String> {
+    Ok(TexInfo {
+        header: tex.header.clone(),
+        image_count: tex.images.len(),
+        mipmap_count: mipmap_count(tex),
+        embedded: embedded_file(tex)?.map(|file| file.extension),
+        frame_count: tex.frame_info.as_ref().map(|info| info.frames.len()),
+    })
+}
+
+pub fn mipmap_count(tex: &Tex) -> usize {
+    tex.images.first().map_or(0, |image| image.mipmaps.len())
+}
+
+// 层级超出范围时使用最小的一级，批量提取时不会因个别贴图层级较少而失败
+pub fn decode_image(tex: &Tex, index: usize, level: usize) -> Result<DecodedImage, String> {
+    if tex.image_format != -1 || tex.is_video_mp4 {
+        return Err("TEX中嵌入的是图像文件，无需解码".to_string());
+    }
+
+    let mipmaps = &tex.images.get(index).ok_or("TEX文件不包含图像数据")?.mipmaps;
+    let level = level.min(mipmaps.len().saturating_sub(1));
+    let mipmap = mipmaps.get(level).ok_or("TEX文件不包含图像数据")?;
+
+    // 实际图像尺寸随层级逐级减半
+    let scale = 1u32 << level.min(31);
+    let image = decode_mipmap(tex.header.format, mipmap)?;
+    Ok(crop(
+        image,
+        tex.header.image_width.div_ceil(scale),
+        tex.header.image_height.div_ceil(scale),
+    ))
+}
+
+fn decode_mipmap(format: TexFormat, mipmap: &TexMipmap) -> Result<DecodedImage, String> {
+    let pixels = mipmap.pixels()?;
+    let color = match format {
+        TexFormat::Rgba8888 => ColorType::Rgba,
+        TexFormat::Rg88 => ColorType::GrayscaleAlpha,
+        TexFormat::R8 => ColorType::Grayscale,
+        TexFormat::Dxt1 | TexFormat::Dxt3 | TexFormat::Dxt5 => {
+            let block_format = match format {
+                TexFormat::Dxt1 => BlockFormat::Dxt1,
+                TexFormat::Dxt3 => BlockFormat::Dxt3,
+                _ => BlockFormat::Dxt5,
+            };
+
+            return Ok(DecodedImage {
+                width: mipmap.width,
+                height: mipmap.height,
+                color: ColorType::Rgba,
+                pixels: dxt::decode(block_format, &pixels, mipmap.width, mipmap.height)?,
+            });
+        }
+        TexFormat::Unknown(value) => return Err(format!("不支持的TEX像素格式: {}", value)),
+    };
+
+    let expected = mipmap.width as usize * mipmap.height as usize * color.bytes_per_pixel();
+    if pixels.len() < expected {
+        return Err(format!("TEX像素数据不完整: 需要 {} 字节，实际 {} 字节", expected, pixels.len()));
+    }
+
+    Ok(DecodedImage {
+        width: mipmap.width,
+        height: mipmap.height,
+        color,
+        pixels: pixels[..expected].to_vec(),
+    })
+}
+
+fn crop(image: DecodedImage, width: u32, height: u32) -> DecodedImage {
+    let width = width.min(image.width);
+    let height = height.min(image.height);
+    if width == 0 || height == 0 || (width == image.width && height == image.height) {
+        return image;
+    }
+
+    let bpp = image.color.bytes_per_pixel();
+    let src_stride = image.width as usize * bpp;
+    let dst_stride = width as usize * bpp;
+    let mut pixels = Vec::with_capacity(dst_stride * height as usize);
+    for row in image.pixels.chunks_exact(src_stride).take(height as usize) {
+        pixels.extend_from_slice(&row[..dst_stride]);
+    }
+
+    DecodedImage {
+        width,
+        height,
+        color: image.color,
+        pixels,
+    }
+}
+
+// TEX 中原样嵌入的图像或视频文件
+pub struct EmbeddedFile<'a> {
+    pub extension: &'static str,
+    pub data: Cow<'a, [u8]>,
+}
+
+// 贴图内容是完整的图像/视频文件时返回其原始字节，无需解码
+pub fn embedded_file(tex: &Tex) -> Result<Option<EmbeddedFile<'_>>, String> {
+    if tex.image_format == -1 && !tex.is_video_mp4 {
+        return Ok(None);
+    }
+
+    let mipmap = tex
+        .images
+        .first()
+        .and_then(|image| image.mipmaps.first())
+        .ok_or("TEX文件不包含图像数据")?;
+    let data = mipmap.pixels()?;
+
+    // 优先根据文件头判断，FreeImage 格式编号作为后备
+    let extension = sniff_extension(&data)
+        .or(if tex.is_video_mp4 { Some("mp4") } else { None })
+        .or_else(|| freeimage_extension(tex.image_format))
+        .unwrap_or("bin");
+
+    Ok(Some(EmbeddedFile { extension, data }))
+}
+
+fn sniff_extension(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("jpg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some("webp")
+    } else if data.len() >= 8 && &data[4..8] == b"ftyp" {
+        Some("mp4")
+    } else if data.starts_with(b"BM") {
+        Some("bmp")
+    } else {
+        None
+    }
+}
+
+fn freeimage_extension(format: i32) -> Option<&'static str> {
+    match format {
+        0 => Some("bmp"),
+        1 => Some("ico"),
+        2 => Some("jpg"),
+        13 => Some("png"),
+        17 => Some("tga"),
+        18 => Some("tiff"),
+        24 => Some("dds"),
+        25 => Some("gif"),
+        35 => Some("webp"),
+        _ => None,
+    }
+}
+
+pub fn encode_png(image: &DecodedImage) -> Result<Vec<u8>, String> {
+    let mut buffer = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buffer, image.width, image.height);
+    encoder.set_color(match image.color {
+        ColorType::Rgba => png::ColorType::Rgba,
+        ColorType::GrayscaleAlpha => png::ColorType::GrayscaleAlpha,
+        ColorType::Grayscale => png::ColorType::Grayscale,
+    });
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("PNG编码失败: {}", e))?;
+    writer
+        .write_image_data(&image.pixels)
+        .map_err(|e| format!("PNG编码失败: {}", e))?;
+    writer.finish().map_err(|e| format!("PNG编码失败: {}", e))?;
+
+    Ok(buffer)
+}
+
+pub fn convert_to_png(tex: &Tex, level: usize) -> Result<Vec<u8>, String> {
+    encode_png(&decode(tex, level)?)
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        ByteReader { bytes, position: 0 }
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("TEX文件已损坏: 数据长度超出文件范围")?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_i32(&mut self) -> Result<i32, String> {
+        self.read_u32().map(|value| value as i32)
+    }
+
+    fn read_f32(&mut self) -> Result<f32, String> {
+        self.read_u32().map(f32::from_bits)
+    }
+
+    fn read_sized_bytes(&mut self) -> Result<Vec<u8>, String> {
+        let length = self.read_u32()? as usize;
+        self.read_bytes(length).map(<[u8]>::to_vec)
+    }
+
+    fn read_nstring(&mut self) -> Result<String, String> {
+        self.read_nstring_max(MAX_MAGIC_LENGTH)
+    }
+
+    fn read_nstring_max(&mut self, max_length: usize) -> Result<String, String> {
+        let remaining = &self.bytes[self.position..];
+        let length = remaining
+            .iter()
+            .take(max_length.saturating_add(1))
+            .position(|b| *b == 0)
+            .ok_or("TEX文件已损坏: 字符串未结束")?;
+        let value = String::from_utf8_lossy(&remaining[..length]).to_string();
+        self.position += length + 1;
+        Ok(value)
+    }
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path};
-use std::process::Command;
+use std::sync::atomic::AtomicBool;
+
+use crate::error::AppError;
+use crate::extract;
+use crate::repkg::ExtractOptions;
 
 #[derive(Serialize, Deserialize)]
 pub struct ImportToEditorOptions {
@@ -19,11 +23,13 @@ pub struct SteamappsPaths {
 }
 
 #[tauri::command]
-pub async fn import_to_wallpaper_editor(options: ImportToEditorOptions) -> Result<String, String> {
+pub async fn import_to_wallpaper_editor(options: ImportToEditorOptions) -> Result<String, AppError> {
     // 获取原始壁纸文件夹路径（scene.pkg的父目录）
     let original_folder = Path::new(&options.scene_pkg_path)
         .parent()
-        .ok_or("无法获取原始文件夹路径")?;
+        .ok_or_else(|| AppError::InvalidInput {
+            message: format!("无法获取原始文件夹路径: {}", options.scene_pkg_path),
+        })?;
     
     // 读取project.json获取title字段
     let original_project_json = original_folder.join("project.json");
@@ -31,10 +37,10 @@ pub async fn import_to_wallpaper_editor(options: ImportToEditorOptions) -> Resul
     
     if original_project_json.exists() {
         let project_content = fs::read_to_string(&original_project_json)
-            .map_err(|e| format!("无法读取project.json: {}", e))?;
+            .map_err(|e| AppError::io(&original_project_json, e))?;
         
         let project_data: serde_json::Value = serde_json::from_str(&project_content)
-            .map_err(|e| format!("project.json格式错误: {}", e))?;
+            .map_err(|e| AppError::json(&original_project_json, e))?;
         
         // 获取title字段作为文件夹名
         if let Some(title) = project_data.get("title").and_then(|t| t.as_str()) {
@@ -62,7 +68,7 @@ pub async fn import_to_wallpaper_editor(options: ImportToEditorOptions) -> Resul
     // 如果目标文件夹不存在则创建
     if !target_folder.exists() {
         fs::create_dir_all(&target_folder)
-            .map_err(|e| format!("无法创建目标文件夹: {}", e))?;
+            .map_err(|e| AppError::io(&target_folder, e))?;
     }
     
     // 构建目标scene.pkg路径
@@ -70,30 +76,31 @@ pub async fn import_to_wallpaper_editor(options: ImportToEditorOptions) -> Resul
     
     // 检查是否已存在且不允许覆盖
     if target_scene_pkg.exists() && !options.overwrite {
-        return Err("目标文件已存在，请启用覆盖选项".to_string());
+        return Err(AppError::AlreadyExists {
+            path: target_scene_pkg.to_string_lossy().to_string(),
+        });
     }
     
     // 复制scene.pkg文件
     fs::copy(&options.scene_pkg_path, &target_scene_pkg)
-        .map_err(|e| format!("无法复制scene.pkg: {}", e))?;
+        .map_err(|e| AppError::io(&target_scene_pkg, e))?;
     
     // 提取scene.pkg到目标文件夹
-    extract_pkg_to_folder(&options.scene_pkg_path, &target_folder.to_string_lossy().to_string())
-        .map_err(|e| format!("无法解压scene.pkg: {}", e))?;
+    extract_pkg_to_folder(&options.scene_pkg_path, target_folder.to_string_lossy().as_ref())?;
     
     // 复制project.json文件
     let target_project_json = target_folder.join("project.json");
     
     if original_project_json.exists() {
         fs::copy(&original_project_json, &target_project_json)
-            .map_err(|e| format!("无法复制project.json: {}", e))?;
+            .map_err(|e| AppError::io(&target_project_json, e))?;
         
         // 读取并修改project.json，删除指定字段
         let project_content = fs::read_to_string(&target_project_json)
-            .map_err(|e| format!("无法读取project.json: {}", e))?;
+            .map_err(|e| AppError::io(&target_project_json, e))?;
         
         let mut project_data: serde_json::Value = serde_json::from_str(&project_content)
-            .map_err(|e| format!("project.json格式错误: {}", e))?;
+            .map_err(|e| AppError::json(&target_project_json, e))?;
         
         // 删除指定的字段
         if let Some(obj) = project_data.as_object_mut() {
@@ -104,10 +111,10 @@ pub async fn import_to_wallpaper_editor(options: ImportToEditorOptions) -> Resul
         
         // 保存修改后的project.json
         let updated_content = serde_json::to_string_pretty(&project_data)
-            .map_err(|e| format!("无法保存project.json: {}", e))?;
+            .map_err(|e| AppError::json(&target_project_json, e))?;
         
         fs::write(&target_project_json, updated_content)
-            .map_err(|e| format!("无法写入project.json: {}", e))?;
+            .map_err(|e| AppError::io(&target_project_json, e))?;
     }
     
     // 复制preview文件（包括各种可能的预览文件格式）
@@ -120,7 +127,7 @@ pub async fn import_to_wallpaper_editor(options: ImportToEditorOptions) -> Resul
             if original_preview.exists() {
                 let target_preview = target_folder.join(format!("{}.{}", name, ext));
                 fs::copy(&original_preview, &target_preview)
-                    .map_err(|e| format!("无法复制预览文件: {}", e))?;
+                    .map_err(|e| AppError::io(&target_preview, e))?;
                 break;
             }
         }
@@ -133,10 +140,12 @@ pub async fn import_to_wallpaper_editor(options: ImportToEditorOptions) -> Resul
 }
 
 #[tauri::command]
-pub async fn check_wallpaper_exists_in_editor(options: ImportToEditorOptions) -> Result<bool, String> {
+pub async fn check_wallpaper_exists_in_editor(options: ImportToEditorOptions) -> Result<bool, AppError> {
     let original_folder = Path::new(&options.scene_pkg_path)
         .parent()
-        .ok_or("无法获取原始文件夹路径")?;
+        .ok_or_else(|| AppError::InvalidInput {
+            message: format!("无法获取原始文件夹路径: {}", options.scene_pkg_path),
+        })?;
     
     // 读取project.json获取title字段
     let original_project_json = original_folder.join("project.json");
@@ -144,10 +153,10 @@ pub async fn check_wallpaper_exists_in_editor(options: ImportToEditorOptions) ->
     
     if original_project_json.exists() {
         let project_content = fs::read_to_string(&original_project_json)
-            .map_err(|e| format!("无法读取project.json: {}", e))?;
+            .map_err(|e| AppError::io(&original_project_json, e))?;
         
         let project_data: serde_json::Value = serde_json::from_str(&project_content)
-            .map_err(|e| format!("project.json格式错误: {}", e))?;
+            .map_err(|e| AppError::json(&original_project_json, e))?;
         
         // 获取title字段作为文件夹名
         if let Some(title) = project_data.get("title").and_then(|t| t.as_str()) {
@@ -174,10 +183,12 @@ pub async fn check_wallpaper_exists_in_editor(options: ImportToEditorOptions) ->
 }
 
 #[tauri::command]
-pub async fn remove_wallpaper_from_editor(options: ImportToEditorOptions) -> Result<String, String> {
+pub async fn remove_wallpaper_from_editor(options: ImportToEditorOptions) -> Result<String, AppError> {
     let original_folder = Path::new(&options.scene_pkg_path)
         .parent()
-        .ok_or("无法获取原始文件夹路径")?;
+        .ok_or_else(|| AppError::InvalidInput {
+            message: format!("无法获取原始文件夹路径: {}", options.scene_pkg_path),
+        })?;
     
     // 读取project.json获取title字段
     let original_project_json = original_folder.join("project.json");
@@ -185,10 +196,10 @@ pub async fn remove_wallpaper_from_editor(options: ImportToEditorOptions) -> Res
     
     if original_project_json.exists() {
         let project_content = fs::read_to_string(&original_project_json)
-            .map_err(|e| format!("无法读取project.json: {}", e))?;
+            .map_err(|e| AppError::io(&original_project_json, e))?;
         
         let project_data: serde_json::Value = serde_json::from_str(&project_content)
-            .map_err(|e| format!("project.json格式错误: {}", e))?;
+            .map_err(|e| AppError::json(&original_project_json, e))?;
         
         // 获取title字段作为文件夹名
         if let Some(title) = project_data.get("title").and_then(|t| t.as_str()) {
@@ -212,17 +223,17 @@ pub async fn remove_wallpaper_from_editor(options: ImportToEditorOptions) -> Res
 
     let target_folder = myprojects_path.join(&folder_name);
     if !target_folder.exists() {
-        return Err("壁纸不存在于编辑器中".to_string());
+        return Err(AppError::not_found(&target_folder));
     }
 
     fs::remove_dir_all(&target_folder)
-        .map_err(|e| format!("无法删除壁纸文件夹: {}", e))?;
+        .map_err(|e| AppError::io(&target_folder, e))?;
 
     Ok(format!("已从壁纸编辑器中删除: {}", target_folder.to_string_lossy()))
 }
 
 #[tauri::command]
-pub async fn get_steamapps_paths() -> Result<SteamappsPaths, String> {
+pub async fn get_steamapps_paths() -> Result<SteamappsPaths, AppError> {
     // 尝试从常见的Steam安装位置查找
     let steam_paths = vec![
         "C:\\Program Files (x86)\\Steam",
@@ -244,7 +255,7 @@ pub async fn get_steamapps_paths() -> Result<SteamappsPaths, String> {
             // 确保myprojects目录存在
             if !myprojects_path.exists() {
                 fs::create_dir_all(&myprojects_path)
-                    .map_err(|e| format!("无法创建myprojects目录: {}", e))?;
+                    .map_err(|e| AppError::io(&myprojects_path, e))?;
             }
             
             return Ok(SteamappsPaths {
@@ -255,11 +266,11 @@ pub async fn get_steamapps_paths() -> Result<SteamappsPaths, String> {
         }
     }
     
-    Err("未检测到Steam或Wallpaper Engine，请确认已正确安装".to_string())
+    Err(AppError::SteamNotFound)
 }
 
 #[tauri::command]
-pub async fn find_workshop_path_from_extract_path(extract_path: String) -> Result<String, String> {
+pub async fn find_workshop_path_from_extract_path(extract_path: String) -> Result<String, AppError> {
     let extract_path = Path::new(&extract_path);
     
     // 向上查找，找到steamapps目录
@@ -275,60 +286,14 @@ pub async fn find_workshop_path_from_extract_path(extract_path: String) -> Resul
     get_steamapps_paths().await.map(|paths| paths.myprojects_path)
 }
 
-fn extract_pkg_to_folder(pkg_path: &str, output_path: &str) -> Result<(), String> {
-    let repkg_path = get_repkg_path();
-    
-    let mut args = vec!["extract", "-o", output_path, "--overwrite"];
-    args.push(pkg_path);
-    
-    let mut command = Command::new(&repkg_path);
-    command.args(&args);
-    
-    // 在 Windows 上隐藏终端窗口
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        command.creation_flags(CREATE_NO_WINDOW);
-    }
-    
-    let output = command
-        .output()
-        .map_err(|e| format!("无法启动RePKG: {}", e))?;
-    
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("RePKG提取失败: {}", stderr));
-    }
-    
-    Ok(())
-}
-
-fn get_repkg_path() -> String {
-    let exe_name = if cfg!(target_os = "windows") {
-        "RePKG.exe"
-    } else {
-        "RePKG"
+fn extract_pkg_to_folder(pkg_path: &str, output_path: &str) -> Result<(), AppError> {
+    let options = ExtractOptions {
+        output: Some(output_path.to_string()),
+        overwrite: true,
+        ..Default::default()
     };
 
-    let mut dev_path = std::env::current_dir().unwrap_or_default();
-    dev_path.push("bin");
-    dev_path.push(exe_name);
-
-    if dev_path.exists() {
-        return dev_path.to_string_lossy().to_string();
-    }
-
-    if let Ok(current_exe) = std::env::current_exe() {
-        if let Some(parent) = current_exe.parent() {
-            let mut prod_path = parent.to_path_buf();
-            prod_path.push(exe_name);
-
-            if prod_path.exists() {
-                return prod_path.to_string_lossy().to_string();
-            }
-        }
-    }
-
-    dev_path.to_string_lossy().to_string()
-}
\ No newline at end of file
+    extract::extract(Path::new(pkg_path), &options, &AtomicBool::new(false), &mut |_| {})
+        .map(|_| ())
+        .map_err(AppError::package)
+}
@@ -96,6 +96,16 @@ class I18n {
     return value;
   }
 
+  // 后端命令返回 {code, ...字段} 形式的错误，按 errors.<code> 翻译，字段作为占位符
+  formatError(error) {
+    if (error && typeof error === 'object' && error.code) {
+      const key = `errors.${error.code}`;
+      const text = this.t(key, error);
+      return text === key ? JSON.stringify(error) : text;
+    }
+    return String(error);
+  }
+
   updateDOM() {
     // Update all elements with data-i18n attribute
     const elements = document.querySelectorAll('[data-i18n]');
@@ -196,7 +196,7 @@ class BackgroundManager {
             const message = window.i18n && window.i18n.t
                 ? window.i18n.t('messages.select_file_error')
                 : '选择文件失败: ';
-            alert(message + error);
+            alert(message + window.i18n.formatError(error));
         }
     }
 
@@ -191,7 +191,7 @@ window.openFolder = async function(path) {
         }
     } catch (error) {
 //         console.error('打开文件夹失败:', error);
-        alert((window.i18n?.t('messages.open_folder_error') || '打开文件夹失败：') + error);
+        alert((window.i18n?.t('messages.open_folder_error') || '打开文件夹失败：') + window.i18n.formatError(error));
     }
 };
 
@@ -230,7 +230,7 @@ window.extractWallpaper = async function(wallpaper) {
             alert(window.i18n?.t('messages.extract_failed') || '提取功能未加载');
         }
     } catch (error) {
-        alert((window.i18n?.t('messages.extract_failed') || '提取失败：') + error);
+        alert((window.i18n?.t('messages.extract_failed') || '提取失败：') + window.i18n.formatError(error));
     }
 };
 
@@ -288,7 +288,7 @@ window.openWorkshop = async function(wallpaper) {
         }
     } catch (error) {
 //         console.error('读取project.json失败:', error);
-        alert((window.i18n?.t('messages.read_project_failed') || '读取项目信息失败：') + error);
+        alert((window.i18n?.t('messages.read_project_failed') || '读取项目信息失败：') + window.i18n.formatError(error));
     }
 };
 
@@ -19,6 +19,48 @@ let sortOrders = {
 
 
 
+// 启动后台提取任务，并等待对应的 extract-finished 事件
+async function runExtractJob(input, options) {
+  const { invoke } = window.__TAURI__.core;
+  const { listen } = window.__TAURI__.event;
+
+  let unlisten = null;
+  let jobId = null;
+  const pending = [];
+
+  const finished = new Promise((resolve, reject) => {
+    const settle = (payload) => {
+      if (unlisten) unlisten();
+      if (payload.error) {
+        reject(payload.error);
+      } else {
+        resolve(payload.summary);
+      }
+    };
+
+    listen('extract-finished', (event) => {
+      // 事件可能早于 invoke 返回任务 ID，先暂存
+      if (jobId === null) {
+        pending.push(event.payload);
+      } else if (event.payload.job_id === jobId) {
+        settle(event.payload);
+      }
+    }).then(async (fn) => {
+      unlisten = fn;
+      try {
+        jobId = await invoke('extract_pkg', { input, options });
+        const early = pending.find((payload) => payload.job_id === jobId);
+        if (early) settle(early);
+      } catch (error) {
+        unlisten();
+        reject(error);
+      }
+    });
+  });
+
+  return finished;
+}
+
 // 设置默认提取路径函数（移到全局作用域）
 async function setDefaultExtractPath() {
   const extractPathInput = document.getElementById('extract-path');
@@ -1036,7 +1078,7 @@ document.addEventListener('DOMContentLoaded', function () {
           await invoke('open_folder', { path: normalizedPath });
         } catch (error) {
           // console.error('打开文件夹失败:', error);
-          alert(window.i18n.t('messages.open_folder_error') + error);
+          alert(window.i18n.t('messages.open_folder_error') + window.i18n.formatError(error));
         }
       };
     }
@@ -1136,7 +1178,7 @@ document.addEventListener('DOMContentLoaded', function () {
       }
 
       // 执行提取
-      const result = await invoke('extract_pkg', { input: scenePkgPath, options: options });
+      const result = await runExtractJob(scenePkgPath, options);
 
       // 添加调试日志
       // console.log('提取结果:', result);
@@ -1194,7 +1236,7 @@ document.addEventListener('DOMContentLoaded', function () {
 
       // 显示错误消息
       // console.error('提取失败:', error);
-      alert(window.i18n.t('messages.extract_failed') + error);
+      alert(window.i18n.t('messages.extract_failed') + window.i18n.formatError(error));
 
       throw error;
     }
@@ -1278,7 +1320,7 @@ document.addEventListener('DOMContentLoaded', function () {
       }
     } catch (error) {
       // console.error('打开文件夹失败:', error);
-      alert(window.i18n.t('messages.open_folder_error') + error);
+      alert(window.i18n.t('messages.open_folder_error') + window.i18n.formatError(error));
     }
   });
 
@@ -1454,7 +1496,7 @@ document.addEventListener('DOMContentLoaded', function () {
       }
     } catch (error) {
       // console.error('选择文件失败:', error);
-      alert(window.i18n.t('messages.select_file_error') + error);
+      alert(window.i18n.t('messages.select_file_error') + window.i18n.formatError(error));
     }
   });
 
@@ -1675,7 +1717,7 @@ document.addEventListener('DOMContentLoaded', function () {
         }
       } catch (error) {
         // console.error('选择文件夹失败:', error);
-        alert(window.i18n.t('messages.select_folder_error') + error);
+        alert(window.i18n.t('messages.select_folder_error') + window.i18n.formatError(error));
       }
     });
   }
@@ -1925,7 +1967,7 @@ async function initExtractPathEvents() {
         }
       } catch (error) {
         // console.error('选择文件夹失败:', error);
-        alert(window.i18n.t('messages.select_folder_error') + error);
+        alert(window.i18n.t('messages.select_folder_error') + window.i18n.formatError(error));
       }
     });
   }
@@ -1966,7 +2008,7 @@ async function initManualExtractPathEvents() {
         }
       } catch (error) {
         // console.error('选择文件夹失败:', error);
-        alert(window.i18n.t('messages.select_folder_error') + error);
+        alert(window.i18n.t('messages.select_folder_error') + window.i18n.formatError(error));
       }
     });
   }
@@ -2255,7 +2297,7 @@ async function initManualExtractFunction() {
 
       } catch (error) {
         // console.error('提取过程出错:', error);
-        alert(window.i18n.t('messages.extract_failed') + error);
+        alert(window.i18n.t('messages.extract_failed') + window.i18n.formatError(error));
 
         // 恢复按钮状态
         manualExtractBtn.textContent = window.i18n.t('buttons.extract_start');
@@ -2282,7 +2324,7 @@ async function initManualExtractFunction() {
         }
       } catch (error) {
         // console.error('打开文件夹失败:', error);
-        alert(window.i18n.t('messages.open_folder_error') + error);
+        alert(window.i18n.t('messages.open_folder_error') + window.i18n.formatError(error));
       }
     });
   }
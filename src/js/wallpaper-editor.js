@@ -122,7 +122,7 @@ class WallpaperEditorImporter {
             this.hideImportProgress();
             // console.error('导入壁纸编辑器失败:', error);
             const failedTitle = window.i18n?.t('messages.import_failed') || '导入失败';
-            alert(`${failedTitle}\n${error}`);
+            alert(`${failedTitle}\n${window.i18n.formatError(error)}`);
         }
     }
 